@@ -1,47 +1,66 @@
-use clap;
-use colored::Colorize;
-
-use crate::config::IntegerOrString;
 use crate::context::RTContext;
 use crate::handleable::{CmdResult, Handleable};
+use crate::operations::{BumpOperation, MultiBumpOperation};
 
-/// Bump profile's version to next generation of given part
+/// Bump profile's version to next generation of given part.
+/// Several profiles are bumped in one transaction with
+/// `--profiles a,b <part>` or `--group <group> <part>`
 #[derive(Debug, clap::Args)]
+#[clap(allow_missing_positional = true)]
 pub struct Bump {
-    #[clap(required = true)]
-    profile: String,
+    /// Profile to bump (omitted if `--profiles` or `--group` is used)
+    #[clap(
+        required_unless_present_any = &["profiles", "group"],
+        conflicts_with_all = &["profiles", "group"]
+    )]
+    profile: Option<String>,
 
+    /// Part to bump. `auto` picks it from Conventional Commits
+    /// made since the last version tag (unless the profile has an `auto` part)
     #[clap(required = true)]
     version_part: String,
 
+    /// Comma-separated profiles bumped in one transaction.
+    /// Patterns like `packages/*:project` match profiles of several members
+    #[clap(long, use_value_delimiter = true, conflicts_with = "group")]
+    profiles: Vec<String>,
+
+    /// Group of profiles from `.weee/groups.toml` bumped in one transaction
+    #[clap(long)]
+    group: Option<String>,
+
     #[clap(long)]
     read_only: bool,
 }
 
 impl Handleable for Bump {
     fn handle(&self, ctx: &mut RTContext) -> CmdResult {
-        let mut profile_ctx = ctx.fetch_profile_conext(&self.profile)?;
-        let changed_version = profile_ctx.bump_version(&self.version_part)?;
-
-        println!(
-            " \u{1F389} Weee! Bumping {} ({} -> {})",
-            self.version_part.cyan(),
-            match &changed_version.old[&self.version_part] {
-                IntegerOrString::Integer(val) => val.to_string(),
-                IntegerOrString::String(val) => val.to_string(),
-            }
-            .red(),
-            match &changed_version.new[&self.version_part] {
-                IntegerOrString::Integer(val) => val.to_string(),
-                IntegerOrString::String(val) => val.to_string(),
-            }
-            .green(),
-        );
-        let prepared_changed_files = profile_ctx.prepare_replacemts(&changed_version)?;
-        profile_ctx.change_files_content(&prepared_changed_files, self.read_only)?;
-        profile_ctx.update_storage(&changed_version, self.read_only)?;
-        profile_ctx.execute_afterword_hooks(&changed_version)?;
+        let profile = match &self.profile {
+            Some(profile) => profile,
+            None => return self.handle_many(ctx),
+        };
+        let report = BumpOperation::new(profile, &self.version_part)
+            .read_only(self.read_only)
+            .run(ctx)?;
+
+        if !ctx.is_text_output() {
+            report.print_json();
+        }
+        Ok(())
+    }
+}
+
+impl Bump {
+    fn handle_many(&self, ctx: &mut RTContext) -> CmdResult {
+        let operation = match &self.group {
+            Some(group) => MultiBumpOperation::group(group, &self.version_part),
+            None => MultiBumpOperation::new(&self.profiles, &self.version_part),
+        };
+        let reports = operation.read_only(self.read_only).run(ctx)?;
 
+        if !ctx.is_text_output() {
+            println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+        }
         Ok(())
     }
 }
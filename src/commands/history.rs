@@ -0,0 +1,47 @@
+use colored::Colorize;
+
+use crate::context::RTContext;
+use crate::handleable::{CmdResult, Handleable};
+use crate::history;
+
+/// List recorded bumps, moves and undos, oldest first
+#[derive(Debug, clap::Args)]
+pub struct History {
+    /// Show entries of this profile only
+    profile: Option<String>,
+}
+
+impl Handleable for History {
+    fn handle(&self, ctx: &mut RTContext) -> CmdResult {
+        let entries: Vec<_> = history::load(&ctx.history_path())?
+            .into_iter()
+            .filter(|entry| {
+                self.profile
+                    .as_ref()
+                    .is_none_or(|profile| entry.profile == *profile)
+            })
+            .collect();
+
+        if !ctx.is_text_output() {
+            println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+            return Ok(());
+        }
+        for entry in entries.iter() {
+            let action = match &entry.bumped_part {
+                Some(part) => format!("{} {}", entry.action.name(), part),
+                None => entry.action.name().to_string(),
+            };
+            println!(
+                "{} {} {} {} -> {} ({} files, {} hooks)",
+                entry.timestamp.bright_black(),
+                entry.profile.cyan(),
+                action.magenta(),
+                entry.old.version.red(),
+                entry.new.version.green(),
+                entry.files.len(),
+                entry.hooks.len(),
+            );
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,3 @@
-use clap;
-
 use crate::context::RTContext;
 use crate::handleable::{CmdResult, Handleable};
 
@@ -11,7 +9,9 @@ pub struct Init {
 }
 
 impl Handleable for Init {
-    fn handle(&self, ctx: &mut RTContext) -> CmdResult {
+    fn handle(&self, _ctx: &mut RTContext) -> CmdResult {
+        // A new project may be created inside an existing one
+        let ctx = RTContext::in_place();
         ctx.create_weee_dir()?;
         let profile_name = match &self.profile {
             Some(name) => name.as_str(),
@@ -1,30 +1,55 @@
-use clap;
 use colored::Colorize;
 use terminal_size::{terminal_size, Height, Width};
 
 mod bump;
+mod history;
 mod init;
 mod r#move;
+mod next;
 mod profile;
+mod schema;
+mod show;
+mod sync;
+mod undo;
+mod validate;
+mod version_format;
 
 use crate::context::RTContext;
+use crate::error::Error;
 use crate::handleable::{CmdResult, Handleable};
+use crate::report::OutputFormat;
 
 #[derive(Debug, clap::Subcommand)]
 enum Commands {
     Bump(bump::Bump),
+    History(history::History),
     // Profile(profile::Profile),
     Init(init::Init),
     Move(r#move::Move),
+    Next(next::Next),
+    Schema(schema::Schema),
+    #[clap(alias = "current")]
+    Show(show::Show),
+    Sync(sync::Sync),
+    Undo(undo::Undo),
+    #[clap(alias = "lint")]
+    Validate(validate::Validate),
 }
 
 impl Handleable for Commands {
     fn handle(&self, ctx: &mut RTContext) -> CmdResult {
         match self {
             Self::Bump(inst) => inst.handle(ctx),
+            Self::History(inst) => inst.handle(ctx),
             Self::Init(inst) => inst.handle(ctx),
             // Self::Profile(inst) => inst.handle(ctx),
             Self::Move(inst) => inst.handle(ctx),
+            Self::Next(inst) => inst.handle(ctx),
+            Self::Schema(inst) => inst.handle(ctx),
+            Self::Show(inst) => inst.handle(ctx),
+            Self::Sync(inst) => inst.handle(ctx),
+            Self::Undo(inst) => inst.handle(ctx),
+            Self::Validate(inst) => inst.handle(ctx),
         }
     }
 }
@@ -38,6 +63,7 @@ impl Handleable for Commands {
 /// i.e. the project version, a dependency version or a language version)
 /// and then use `bump` or `move` commands to update it.
 /// Refer to the repo to see more information: https://github.com/deknowny/weee.
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, clap::Parser)]
 #[clap(
     name = "weee",
@@ -46,32 +72,63 @@ impl Handleable for Commands {
 pub struct CLI {
     #[clap(subcommand)]
     command: Commands,
+
+    /// How commands report what they have done
+    #[clap(long, arg_enum, global = true, default_value = "text")]
+    output: OutputFormat,
+
+    /// How errors should be reported to stderr
+    #[clap(long, arg_enum, global = true, default_value = "text")]
+    error_format: ErrorFormat,
+}
+
+#[derive(Debug, Clone, clap::ArgEnum)]
+enum ErrorFormat {
+    Text,
+    Json,
 }
 
 impl CLI {
-    pub fn handle(self) {
+    /// Run the command and return the process exit code
+    pub fn handle(self) -> i32 {
         let mut context = RTContext::new();
+        context.output = self.output.clone();
         let result = self.command.handle(&mut context);
 
-        if let Err(err) = result {
-            let term_size = terminal_size();
-            let header;
-            if let Some((Width(w), Height(_))) = term_size {
-                let header_block = format!(" [ {} ] ", err.title.red());
-                let line = std::iter::repeat("-")
-                    .take((w as usize - err.title.len() - 6) / 2)
-                    .collect::<String>();
-                header = format!("{}{}{}", line, header_block, line)
-            } else {
-                header = format!("[ {} ]", err.title.red())
+        match result {
+            Ok(()) => 0,
+            Err(err) => {
+                match self.error_format {
+                    ErrorFormat::Text => Self::render_error(&err),
+                    ErrorFormat::Json => eprintln!("{}", err.to_json()),
+                }
+                err.exit_code()
             }
+        }
+    }
 
-            let mut payload = String::new();
-            for (key, value) in err.payload.iter() {
-                payload.push_str(format!("\n [{}]: {}", key.magenta(), value).as_str());
-            }
+    fn render_error(err: &Error) {
+        let title = err.title();
+        let term_size = terminal_size();
+        let header;
+        if let Some((Width(w), Height(_))) = term_size {
+            let header_block = format!(" [ {} ] ", title.red());
+            let line = "-".repeat((w as usize - title.len() - 6) / 2);
+            header = format!("{}{}{}", line, header_block, line)
+        } else {
+            header = format!("[ {} ]", title.red())
+        }
 
-            eprintln!("{}\n => {}\n{}", header, err.description.yellow(), payload);
+        let mut payload = String::new();
+        for (key, value) in err.payload().iter() {
+            payload.push_str(format!("\n [{}]: {}", key.magenta(), value).as_str());
         }
+
+        eprintln!(
+            "{}\n => {}\n{}",
+            header,
+            err.description().yellow(),
+            payload
+        );
     }
 }
@@ -0,0 +1,47 @@
+use crate::commands::version_format::VersionFormat;
+use crate::context::RTContext;
+use crate::handleable::{CmdResult, Handleable};
+
+/// Print the version that `bump` would produce.
+/// Neither files, nor the profile, nor hooks are touched
+#[derive(Debug, clap::Args)]
+pub struct Next {
+    /// Profile that would be used
+    #[clap(required = true)]
+    profile: String,
+
+    #[clap(required = true)]
+    version_part: String,
+
+    #[clap(flatten)]
+    output: VersionFormat,
+}
+
+impl Handleable for Next {
+    fn handle(&self, ctx: &mut RTContext) -> CmdResult {
+        let profile_ctx = ctx.fetch_profile_conext(&self.profile)?;
+        let changed_version = profile_ctx.bump_version(&self.version_part)?;
+
+        if self.output.is_json(ctx) {
+            let mut document = self
+                .output
+                .json_document(&profile_ctx, &changed_version.new)?;
+            document.insert("bumped_part".into(), self.version_part.clone().into());
+            document.insert(
+                "old".into(),
+                serde_json::json!({
+                    "version": profile_ctx.version_to_string(&changed_version.old),
+                    "parts": changed_version.old,
+                }),
+            );
+            VersionFormat::print_json(document);
+        } else {
+            println!(
+                "{}",
+                self.output.render(&profile_ctx, &changed_version.new)?
+            );
+        }
+
+        Ok(())
+    }
+}
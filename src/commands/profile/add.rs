@@ -1,5 +1,3 @@
-use clap;
-
 use crate::context::RTContext;
 use crate::handleable::{CmdResult, Handleable};
 
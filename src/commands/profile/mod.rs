@@ -1,5 +1,3 @@
-use clap;
-
 mod add;
 mod remove;
 
@@ -1,5 +1,3 @@
-use clap;
-
 #[derive(Debug, clap::Args)]
 pub struct Remove {
     #[clap(required = true)]
@@ -0,0 +1,18 @@
+use crate::context::RTContext;
+use crate::handleable::{CmdResult, Handleable};
+use crate::schema;
+
+/// Print JSON Schema of profile files, i.e. to let editors validate
+/// and autocomplete `.weee/*.version.toml` (taplo, Even Better TOML)
+#[derive(Debug, clap::Args)]
+pub struct Schema {}
+
+impl Handleable for Schema {
+    fn handle(&self, _ctx: &mut RTContext) -> CmdResult {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&schema::profile_schema()).unwrap()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use crate::commands::version_format::VersionFormat;
+use crate::context::RTContext;
+use crate::handleable::{CmdResult, Handleable};
+
+/// Print profile's current version
+#[derive(Debug, clap::Args)]
+pub struct Show {
+    /// Profile that would be used
+    #[clap(required = true)]
+    profile: String,
+
+    #[clap(flatten)]
+    output: VersionFormat,
+}
+
+impl Handleable for Show {
+    fn handle(&self, ctx: &mut RTContext) -> CmdResult {
+        let profile_ctx = ctx.fetch_profile_conext(&self.profile)?;
+        let version = profile_ctx.current_version();
+
+        if self.output.is_json(ctx) {
+            VersionFormat::print_json(self.output.json_document(&profile_ctx, &version)?);
+        } else {
+            println!("{}", self.output.render(&profile_ctx, &version)?);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+use colored::Colorize;
+
+use crate::context::{ChangedVersion, RTContext};
+use crate::handleable::{CmdResult, Handleable};
+
+/// Update the stored version from another source of truth.
+/// Only the profile is changed, files are kept as they are
+#[derive(Debug, clap::Args)]
+#[clap(group(clap::ArgGroup::new("source").required(true).args(&["from-git-tag"])))]
+pub struct Sync {
+    /// Profile that would be used
+    #[clap(required = true)]
+    profile: String,
+
+    /// Take the version from the highest git tag parsed with the profile's first view
+    #[clap(long)]
+    from_git_tag: bool,
+
+    /// Glob pattern of tags. Its literal prefix (`v` in `v*`) is stripped before parsing.
+    /// Defaults to the pattern of version tags (see `conventional_commits.tag_pattern`)
+    #[clap(long, requires = "from-git-tag")]
+    pattern: Option<String>,
+
+    /// Do not change the profile, only show how it would be changed
+    #[clap(long)]
+    read_only: bool,
+}
+
+impl Handleable for Sync {
+    fn handle(&self, ctx: &mut RTContext) -> CmdResult {
+        let mut profile_ctx = ctx.fetch_profile_conext(&self.profile)?;
+        let pattern = match &self.pattern {
+            Some(pattern) => pattern.clone(),
+            None => profile_ctx.version_tag_pattern(),
+        };
+        let (tag, new_version) = profile_ctx.latest_git_tag(&pattern)?;
+        let changed_version = ChangedVersion {
+            old: profile_ctx.current_version(),
+            new: new_version,
+        };
+        profile_ctx.update_storage(&changed_version, self.read_only)?;
+
+        let old = profile_ctx.version_to_string(&changed_version.old);
+        let new = profile_ctx.version_to_string(&changed_version.new);
+        if ctx.is_text_output() {
+            let read_only = if self.read_only { " (read-only)" } else { "" };
+            if changed_version.old == changed_version.new {
+                println!(
+                    " \u{1F44C} Profile {} is already in sync with tag {}{}",
+                    self.profile.cyan(),
+                    tag.cyan(),
+                    read_only
+                );
+            } else {
+                println!(
+                    " \u{1F504} Synced profile {} with tag {} ({} -> {}){}",
+                    self.profile.cyan(),
+                    tag.cyan(),
+                    old.red(),
+                    new.green(),
+                    read_only
+                );
+            }
+        } else {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&serde_json::json!({
+                    "profile": self.profile,
+                    "tag": tag,
+                    "read_only": self.read_only,
+                    "changed": changed_version.old != changed_version.new,
+                    "old": {"version": old, "parts": changed_version.old},
+                    "new": {"version": new, "parts": changed_version.new},
+                }))
+                .unwrap()
+            );
+        }
+        Ok(())
+    }
+}
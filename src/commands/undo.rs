@@ -0,0 +1,69 @@
+use colored::Colorize;
+
+use crate::context::{self, ChangedVersion, RTContext};
+use crate::error::Error;
+use crate::handleable::{CmdResult, Handleable};
+use crate::history;
+use crate::report::Report;
+
+/// Revert the last bump or move recorded in the history.
+/// Profiles bumped together are reverted together.
+/// Files are changed back with reversed replacements, hooks are not executed
+/// and git commits or tags are kept as they are
+#[derive(Debug, clap::Args)]
+pub struct Undo {
+    /// Do not any changes in files,
+    /// only show how it would be changed
+    #[clap(long)]
+    read_only: bool,
+}
+
+impl Handleable for Undo {
+    fn handle(&self, ctx: &mut RTContext) -> CmdResult {
+        let entries = history::load(&ctx.history_path())?;
+        let transaction = match history::last_undoable(&entries) {
+            Some(transaction) => transaction,
+            None => return Err(Error::NothingToUndo),
+        };
+
+        let mut undone = vec![];
+        for entry in transaction {
+            let profile_ctx = ctx.fetch_profile_conext(&entry.profile)?;
+            let changed_version = ChangedVersion {
+                old: entry.new.parts.clone(),
+                new: entry.old.parts.clone(),
+            };
+            let report = Report::new(
+                &profile_ctx,
+                entry.bumped_part.as_deref(),
+                &changed_version,
+                self.read_only,
+            );
+
+            if ctx.is_text_output() {
+                println!(
+                    " \u{23EA} Undoing {} of {} from {} ({} -> {})",
+                    entry.action.name().magenta(),
+                    entry.profile.cyan(),
+                    entry.timestamp.bright_black(),
+                    entry.new.version.red(),
+                    entry.old.version.green(),
+                );
+            }
+            undone.push((profile_ctx, entry, report));
+        }
+        context::undo_transaction(&mut undone, self.read_only)?;
+
+        if !ctx.is_text_output() {
+            match undone.as_slice() {
+                [(_profile_ctx, _entry, report)] => report.print_json(),
+                _ => {
+                    let reports: Vec<&Report> =
+                        undone.iter().map(|(_ctx, _entry, report)| report).collect();
+                    println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+                }
+            }
+        }
+        Ok(())
+    }
+}
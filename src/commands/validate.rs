@@ -0,0 +1,76 @@
+use colored::Colorize;
+
+use crate::context::RTContext;
+use crate::error::Error;
+use crate::handleable::{CmdResult, Handleable};
+use crate::operations::ValidateOperation;
+
+/// Check profiles' configuration without touching the project:
+/// the config parses, files exist, views reference defined parts,
+/// liquid templates compile and hook executables are available.
+/// Exits with a non-zero code if any problem is found
+#[derive(Debug, clap::Args)]
+#[clap(group(clap::ArgGroup::new("profiles").required(true).args(&["profile", "all"])))]
+pub struct Validate {
+    /// Profile that would be checked
+    profile: Option<String>,
+
+    /// Check every profile in `.weee` and in workspace members' ones
+    #[clap(long)]
+    all: bool,
+}
+
+impl Handleable for Validate {
+    fn handle(&self, ctx: &mut RTContext) -> CmdResult {
+        let operation = match &self.profile {
+            Some(profile) => ValidateOperation::new(profile),
+            None => ValidateOperation::all(),
+        };
+        let reports = operation.run(ctx)?;
+
+        if ctx.is_text_output() {
+            for report in reports.iter() {
+                if report.problems.is_empty() {
+                    println!(" \u{1F44C} {}: no problems found", report.profile.cyan());
+                    continue;
+                }
+                println!(
+                    " \u{1F4A5} {}: {} problem(s) found",
+                    report.profile.cyan(),
+                    report.problems.len()
+                );
+                let (project_ctx, name) = ctx.locate_profile(&report.profile)?;
+                let profile_path = project_ctx.profile_path(name).display().to_string();
+                for problem in report.problems.iter() {
+                    let file = problem.file.as_ref().unwrap_or(&profile_path);
+                    let location = match (problem.line, problem.column) {
+                        (Some(line), Some(column)) => format!("{}:{}:{}: ", file, line, column),
+                        _ => String::new(),
+                    };
+                    println!(
+                        "    [{}] {}{}",
+                        problem.check.magenta(),
+                        location,
+                        problem.message
+                    );
+                }
+            }
+        } else {
+            println!("{}", serde_json::to_string_pretty(&reports).unwrap());
+        }
+
+        let failed: Vec<String> = reports
+            .iter()
+            .filter(|report| !report.problems.is_empty())
+            .map(|report| report.profile.clone())
+            .collect();
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::ValidationFailed {
+                problems: reports.iter().map(|report| report.problems.len()).sum(),
+                profiles: failed,
+            })
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use crate::context::{ProfileContext, RTContext, Version};
+use crate::error::Error;
+use crate::handleable::CmdResult;
+
+/// Options describing how a version should be printed
+#[derive(Debug, clap::Args)]
+pub struct VersionFormat {
+    /// Render the version the same way as it's written in this file
+    #[clap(long, conflicts_with_all = &["format", "json"])]
+    file: Option<String>,
+
+    /// Which of the file's views should be used if the file has many
+    #[clap(long, requires = "file", default_value_t = 0)]
+    nth: usize,
+
+    /// Custom view like `{major}.{minor}`
+    #[clap(long, conflicts_with = "json")]
+    format: Option<String>,
+
+    /// Treat `--format` as a liquid template
+    #[clap(long, requires = "format")]
+    liquid: bool,
+
+    /// Print the version and all of its parts as JSON, same as `--output json`
+    #[clap(long)]
+    json: bool,
+}
+
+impl VersionFormat {
+    /// Whether the version is printed as JSON: either with `--json`
+    /// or with the global `--output json`
+    pub fn is_json(&self, ctx: &RTContext) -> bool {
+        self.json || !ctx.is_text_output()
+    }
+
+    pub fn render(&self, profile_ctx: &ProfileContext, version: &Version) -> CmdResult<String> {
+        if let Some(file_name) = &self.file {
+            let file = match profile_ctx
+                .profile_model
+                .files
+                .get(file_name)
+                .and_then(|views| views.get(self.nth))
+            {
+                Some(file) => file,
+                None => {
+                    return Err(Error::NoSuchFileView {
+                        profile: profile_ctx.profile_name.clone(),
+                        file: file_name.clone(),
+                        nth: self.nth,
+                    })
+                }
+            };
+            profile_ctx.render_view(
+                version,
+                &file.version.view,
+                file.enable_liquid_tempaltes.unwrap_or_default(),
+                file_name,
+            )
+        } else if let Some(format) = &self.format {
+            profile_ctx.render_view(version, format, self.liquid, "<--format>")
+        } else {
+            Ok(profile_ctx.version_to_string(version))
+        }
+    }
+
+    /// JSON object with the profile name, the version string and all the parts.
+    /// The version string is the same as the text output, so with `--file` or `--format`
+    /// it's the chosen view (also kept in `view`).
+    /// Callers may extend it with their own fields before printing
+    pub fn json_document(
+        &self,
+        profile_ctx: &ProfileContext,
+        version: &Version,
+    ) -> CmdResult<serde_json::Map<String, serde_json::Value>> {
+        let mut document = serde_json::Map::new();
+        document.insert("profile".into(), profile_ctx.qualified_name().into());
+        let rendered = self.render(profile_ctx, version)?;
+        document.insert("version".into(), rendered.clone().into());
+        document.insert("parts".into(), serde_json::json!(version));
+        if self.file.is_some() || self.format.is_some() {
+            document.insert("view".into(), rendered.into());
+        }
+        Ok(document)
+    }
+
+    pub fn print_json(document: serde_json::Map<String, serde_json::Value>) {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::Value::Object(document)).unwrap()
+        );
+    }
+}
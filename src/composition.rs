@@ -0,0 +1,182 @@
+//! Profiles made of several files under `.weee/`:
+//!
+//! ```toml
+//! extends = "base"                   # .weee/base.version.toml
+//! include = ["common-hooks.toml"]    # .weee/common-hooks.toml
+//! ```
+//!
+//! Sources are merged in this order: the extended profile (with everything
+//! it extends and includes itself), included files in the listed order and
+//! the profile itself. Later sources override earlier ones:
+//! tables (`parts`, `files`, `hooks`, a part, a hook...) are merged key by key,
+//! anything else (values, arrays and `[[files."..."]]` views) is replaced as a whole.
+//! Parts keep the order they are first defined in.
+//! New part values are always written into the profile's own file.
+
+use std::path::PathBuf;
+
+use serde_derive::Deserialize;
+use toml_edit::{Document, Item, Table, TableLike};
+
+use crate::config::ProfileConfig;
+use crate::context::RTContext;
+use crate::diagnostics::{self, Diagnostic};
+use crate::error::Error;
+use crate::handleable::CmdResult;
+
+/// Keys describing where other sources of a profile are
+#[derive(Deserialize, Default)]
+struct Composition {
+    extends: Option<String>,
+    include: Option<Vec<String>>,
+}
+
+pub struct ComposedProfile {
+    /// The profile's own file
+    pub own: Document,
+    /// Everything merged, without `extends` and `include`
+    pub merged: Document,
+    /// Files merged into the profile, the profile's own file is the last one
+    pub sources: Vec<PathBuf>,
+    /// Contents of `sources`
+    contents: Vec<String>,
+}
+
+impl ComposedProfile {
+    /// Deserialize and check the merged profile.
+    /// Problems point to the sources values come from
+    pub fn check(&self) -> Result<ProfileConfig, Vec<Diagnostic>> {
+        let sources: Vec<(&std::path::Path, &str)> = self
+            .sources
+            .iter()
+            .zip(self.contents.iter())
+            .map(|(path, content)| (path.as_path(), content.as_str()))
+            .collect();
+        diagnostics::check_merged_profile(&self.merged.to_string(), &sources)
+    }
+}
+
+pub fn compose(ctx: &RTContext, profile: &str) -> CmdResult<ComposedProfile> {
+    let mut sources = vec![];
+    let mut chain = vec![];
+    let (own, merged) = resolve(
+        ctx,
+        profile,
+        Source::Profile(profile),
+        &mut chain,
+        &mut sources,
+    )?;
+    let (sources, contents) = sources.into_iter().unzip();
+    Ok(ComposedProfile {
+        own,
+        merged,
+        sources,
+        contents,
+    })
+}
+
+#[derive(Clone, Copy)]
+enum Source<'a> {
+    Profile(&'a str),
+    Include(&'a str),
+}
+
+/// Own document of the source and the source merged with everything it refers to
+fn resolve(
+    ctx: &RTContext,
+    profile: &str,
+    source: Source,
+    chain: &mut Vec<PathBuf>,
+    sources: &mut Vec<(PathBuf, String)>,
+) -> CmdResult<(Document, Document)> {
+    let (path, name) = match source {
+        Source::Profile(name) => (ctx.profile_path(name), name),
+        Source::Include(name) => (ctx.project_file_path(&format!(".weee/{}", name)), name),
+    };
+    if chain.contains(&path) {
+        let mut cycle: Vec<String> = chain
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        cycle.push(path.display().to_string());
+        return Err(Error::ProfileInheritanceCycle {
+            profile: profile.to_string(),
+            cycle,
+        });
+    }
+
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) => {
+            return Err(match (err.kind(), source) {
+                (std::io::ErrorKind::NotFound, Source::Profile(_)) => Error::NoSuchProfileExists {
+                    profile: name.to_string(),
+                    path,
+                },
+                _ => Error::CannotReadProfile {
+                    profile: profile.to_string(),
+                    path,
+                    source: err,
+                },
+            })
+        }
+    };
+    let own = match content.parse::<Document>() {
+        Ok(doc) => doc,
+        Err(err) => {
+            return Err(Error::TOMLInvalidSyntax {
+                profile: profile.to_string(),
+                path,
+                source: err,
+            })
+        }
+    };
+    let composition = match toml::from_str::<Composition>(&content) {
+        Ok(composition) => composition,
+        Err(err) => {
+            return Err(Error::InvalidProfileConfig {
+                profile: profile.to_string(),
+                diagnostics: vec![Diagnostic::from_toml_error(&content, &err)],
+                sources: vec![path.clone()],
+                path,
+            })
+        }
+    };
+
+    chain.push(path.clone());
+    let mut merged = Document::new();
+    if let Some(base) = &composition.extends {
+        let (_base_own, base_merged) =
+            resolve(ctx, profile, Source::Profile(base), chain, sources)?;
+        merge_tables(merged.as_table_mut(), base_merged.as_table());
+    }
+    for include in composition.include.iter().flatten() {
+        let (_include_own, include_merged) =
+            resolve(ctx, profile, Source::Include(include), chain, sources)?;
+        merge_tables(merged.as_table_mut(), include_merged.as_table());
+    }
+    chain.pop();
+
+    merge_tables(merged.as_table_mut(), own.as_table());
+    merged.as_table_mut().remove("extends");
+    merged.as_table_mut().remove("include");
+    sources.push((path, content));
+    Ok((own, merged))
+}
+
+/// Merge `overlay` into `base`. Tables (inline ones too) are merged recursively,
+/// anything else from `overlay` replaces the value in `base`
+fn merge_tables(base: &mut Table, overlay: &dyn TableLike) {
+    for (key, item) in overlay.iter() {
+        if let (Some(base_item), Some(overlay_table)) = (base.get_mut(key), item.as_table_like()) {
+            if let Some(inline) = base_item.as_inline_table() {
+                *base_item = Item::Table(inline.clone().into_table());
+            }
+            if let Item::Table(base_table) = base_item {
+                merge_tables(base_table, overlay_table);
+                continue;
+            }
+        }
+        base.insert(key, item.clone());
+    }
+}
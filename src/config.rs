@@ -9,11 +9,11 @@ pub enum IntegerOrString<T> {
     Integer(T),
 }
 
-impl<T: std::string::ToString> std::string::ToString for IntegerOrString<T> {
-    fn to_string(&self) -> String {
+impl<T: std::fmt::Display> std::fmt::Display for IntegerOrString<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
-            IntegerOrString::Integer(val) => val.to_string(),
-            IntegerOrString::String(val) => val.to_string(),
+            IntegerOrString::Integer(val) => val.fmt(f),
+            IntegerOrString::String(val) => val.fmt(f),
         }
     }
 }
@@ -23,6 +23,11 @@ pub struct ProfileConfig {
     pub parts: LinkedHashMap<String, Part>,
     pub files: LinkedHashMap<String, Vec<File>>,
     pub hooks: Option<Hooks>,
+    pub git: Option<Git>,
+    pub conventional_commits: Option<ConventionalCommits>,
+    pub changelog: Option<Changelog>,
+    /// Profiles whose versions this one refers to, by name
+    pub dependencies: Option<LinkedHashMap<String, Dependency>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
@@ -53,9 +58,183 @@ pub struct FileVersion {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Hooks {
-    pub afterwords: Option<LinkedHashMap<String, Vec<String>>>,
+    /// Executed when the new version is known but no files are changed yet.
+    /// A failed hook aborts the bump
+    pub before: Option<LinkedHashMap<String, Hook>>,
+    pub afterwords: Option<LinkedHashMap<String, Hook>>,
+    /// Executed when replacing versions in files or an afterword hook failed
+    pub on_failure: Option<LinkedHashMap<String, Hook>>,
+    /// Save full output of every hook into `.weee/logs/`
+    pub save_logs: Option<bool>,
 }
 
+/// A hook is either a plain command (`["git", "tag", "v1"]`)
+/// or a table with the command and conditions when it should be executed
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum Hook {
+    Args(Vec<String>),
+    Detailed(Box<DetailedHook>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DetailedHook {
+    /// Command with arguments. Conflicts with `shell`
+    pub args: Option<Vec<String>>,
+    /// Command line executed with `sh -c` (`cmd /C` on Windows)
+    pub shell: Option<String>,
+    /// Working directory relative to the project's root
+    pub cwd: Option<String>,
+    pub timeout_secs: Option<u64>,
+    pub env: Option<LinkedHashMap<String, String>>,
+    /// Do not abort the bump if the hook failed
+    pub allow_failure: Option<bool>,
+    /// How many times the hook is re-executed if it failed
+    pub retries: Option<u32>,
+
+    /// Execute only if one of these parts is bumped
+    pub only_parts: Option<Vec<String>>,
+    /// Execute only for these profiles
+    pub only_profiles: Option<Vec<String>>,
+    /// Liquid predicate like `{{ new.stage == 'final' }}`
+    pub when: Option<String>,
+    pub skip_in_read_only: Option<bool>,
+}
+
+impl Hook {
+    /// Command with arguments before any `!PREFIX:` processing
+    pub fn command(&self) -> Result<Vec<String>, &'static str> {
+        let command = match self {
+            Hook::Args(args) => args.clone(),
+            Hook::Detailed(hook) => match (&hook.args, &hook.shell) {
+                (Some(args), None) => args.clone(),
+                (None, Some(shell)) => shell_command(shell),
+                (Some(_), Some(_)) => return Err("Hook cannot have both `args` and `shell`"),
+                (None, None) => return Err("Hook should have either `args` or `shell`"),
+            },
+        };
+        if command.is_empty() {
+            return Err("Hook's command cannot be empty");
+        }
+        Ok(command)
+    }
+
+    pub fn detailed(&self) -> Option<&DetailedHook> {
+        match self {
+            Hook::Args(_) => None,
+            Hook::Detailed(hook) => Some(hook),
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell_command(line: &str) -> Vec<String> {
+    vec!["sh".into(), "-c".into(), line.into()]
+}
+
+#[cfg(target_os = "windows")]
+fn shell_command(line: &str) -> Vec<String> {
+    vec!["cmd".into(), "/C".into(), line.into()]
+}
+
+/// Built-in git integration. Checks are done before any file is changed,
+/// commit and tag are created right after files are changed (before afterword hooks)
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Git {
+    /// Refuse to bump if there are changes in files not managed by the profile
+    pub require_clean: Option<bool>,
+    /// Commit files changed by weee (and only them)
+    pub commit: Option<bool>,
+    /// Liquid template of the commit message
+    pub commit_message: Option<String>,
+    /// Liquid template of a tag name. No tag is created if it's not set.
+    /// Requires `commit`, so the tag is put on the commit with the new version
+    pub tag: Option<String>,
+    /// Liquid template of the tag message. The tag is annotated if it's set
+    pub tag_message: Option<String>,
+}
+
+pub const DEFAULT_COMMIT_MESSAGE: &str = "Bump version: {{ old_version }} → {{ new_version }}";
+
+/// How `bump <profile> auto` picks a part from Conventional Commits
+/// made since the last version tag
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ConventionalCommits {
+    /// Commit type to part, i.e. `feat = "minor"`.
+    /// Defaults to `feat` → `minor` and `fix` → `patch`
+    pub types: Option<LinkedHashMap<String, String>>,
+    /// Part bumped for breaking changes (`feat!:` or a `BREAKING CHANGE:` footer).
+    /// Defaults to `major`
+    pub breaking: Option<String>,
+    /// Glob pattern of version tags. Defaults to the `git.tag` template
+    /// with liquid replaced by `*` (i.e. `v*`), or `v*` if there is no `git.tag`
+    pub tag_pattern: Option<String>,
+}
+
+impl ConventionalCommits {
+    pub fn part_of_type(&self, commit_type: &str) -> Option<String> {
+        match &self.types {
+            Some(types) => types.get(commit_type).cloned(),
+            None => match commit_type {
+                "feat" => Some("minor".into()),
+                "fix" => Some("patch".into()),
+                _ => None,
+            },
+        }
+    }
+
+    pub fn breaking_part(&self) -> String {
+        self.breaking.clone().unwrap_or_else(|| "major".into())
+    }
+}
+
+/// Promotes the "Unreleased" section of a changelog on every bump.
+/// The changelog is changed together with the other files
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Changelog {
+    /// Path relative to the project's root. Defaults to `CHANGELOG.md`
+    pub path: Option<String>,
+    /// Heading of the section with unreleased changes. Defaults to `## [Unreleased]`
+    pub unreleased: Option<String>,
+    /// Liquid template of the released section's heading.
+    /// Variables are the same as for hooks
+    pub heading: Option<String>,
+    /// Fill the released section from Conventional Commits since the last version tag
+    pub from_commits: Option<bool>,
+    /// Commit type to section title, i.e. `feat = "Added"`.
+    /// Defaults to `feat` → `Added` and `fix` → `Fixed`
+    pub sections: Option<LinkedHashMap<String, String>>,
+}
+
+impl Changelog {
+    pub fn section_of_type(&self, commit_type: &str) -> Option<String> {
+        match &self.sections {
+            Some(sections) => sections.get(commit_type).cloned(),
+            None => match commit_type {
+                "feat" => Some("Added".into()),
+                "fix" => Some("Fixed".into()),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// References to another profile's version, i.e. the constraint `mylib = "^1.3"`
+/// in `Cargo.toml`. They are rewritten whenever the dependency is bumped
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Dependency {
+    /// Part of this profile bumped together with the dependency
+    pub bump: Option<String>,
+    /// Views of the dependency's version (with the dependency's parts)
+    /// in files relative to this profile's root
+    #[serde(default)]
+    pub files: LinkedHashMap<String, Vec<File>>,
+}
+
+pub const DEFAULT_CHANGELOG_PATH: &str = "CHANGELOG.md";
+pub const DEFAULT_UNRELEASED_HEADING: &str = "## [Unreleased]";
+pub const DEFAULT_CHANGELOG_HEADING: &str = "## [{{ new_version }}] - {{ date.iso }}";
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct IncrementPayload {
     pub default: Option<u64>,
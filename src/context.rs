@@ -1,18 +1,32 @@
-use std;
 use std::io::Write;
 
 use colored::Colorize;
 use linked_hash_map::LinkedHashMap;
-use liquid;
 use liquid::model::ScalarCow;
+use serde_derive::{Deserialize, Serialize};
 use toml_edit::Document;
 
-use crate::config::{Factory, File, IntegerOrString, Part, ProfileConfig};
-use crate::error::CLIError;
+use crate::composition;
+use crate::config::{
+    Changelog, ConventionalCommits, Factory, File, Hook, IntegerOrString, Part, ProfileConfig,
+    DEFAULT_CHANGELOG_HEADING, DEFAULT_CHANGELOG_PATH, DEFAULT_COMMIT_MESSAGE,
+    DEFAULT_UNRELEASED_HEADING,
+};
+use crate::error::Error;
+use crate::git;
+use crate::groups;
 use crate::handleable::CmdResult;
-use crate::show_err;
+use crate::history::{
+    self, HistoryChangelog, HistoryDependency, HistoryEntry, HistoryFile, HistoryHook,
+};
+use crate::process;
+use crate::report::{
+    AutoReport, ChangelogReport, CommitReport, FileReport, GitReport, HookReport, OutputFormat,
+    Report,
+};
+use crate::workspace;
 
-type Version = LinkedHashMap<String, IntegerOrString<u64>>;
+pub type Version = LinkedHashMap<String, IntegerOrString<u64>>;
 
 #[derive(Clone, Debug)]
 pub struct ChangedVersion {
@@ -20,6 +34,55 @@ pub struct ChangedVersion {
     pub new: Version,
 }
 
+fn part_to_liquid(value: &IntegerOrString<u64>) -> liquid::model::Value {
+    liquid::model::Value::Scalar(match value {
+        IntegerOrString::Integer(value) => ScalarCow::new(*value as i64),
+        IntegerOrString::String(value) => ScalarCow::new(value.clone()),
+    })
+}
+
+/// Split `<value>|<default>` used by interactive argument sources
+fn split_default(source: &str) -> (&str, Option<&str>) {
+    match source.rsplit_once('|') {
+        Some((value, default)) => (value, Some(default)),
+        None => (source, None),
+    }
+}
+
+/// Part name as a part of an environment variable name, i.e. `pre-release` => `PRE_RELEASE`
+fn env_name(part: &str) -> String {
+    part.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Liquid has no expressions in output blocks,
+/// so hook's `when = "{{ new.stage == 'final' }}"` is treated as an if-condition
+pub(crate) fn when_template(when: &str) -> String {
+    let trimmed = when.trim();
+    match trimmed
+        .strip_prefix("{{")
+        .and_then(|rest| rest.strip_suffix("}}"))
+    {
+        Some(condition) => format!("{{% if {} %}}true{{% endif %}}", condition),
+        None => trimmed.to_string(),
+    }
+}
+
+fn version_to_liquid(version: &Version) -> liquid::model::Value {
+    let mut object = liquid::Object::new();
+    for (key, value) in version {
+        object.insert(key.clone().into(), part_to_liquid(value));
+    }
+    liquid::model::Value::Object(object)
+}
+
 #[derive(Clone, Debug)]
 pub struct ChangedFile {
     pub name: String,
@@ -27,22 +90,187 @@ pub struct ChangedFile {
     pub old_version: String,
     pub new_part: String,
     pub new_version: String,
+    /// How many entries of the old part are replaced (all of them if it's not set)
+    pub replaces_count: Option<u64>,
+}
+
+/// New contents of files kept in memory until every change has succeeded,
+/// so a failure never leaves the project half-changed.
+/// A file changed by several profiles is written once with all the changes
+#[derive(Default)]
+pub struct PendingFiles {
+    contents: LinkedHashMap<std::path::PathBuf, String>,
+    /// Contents written files had before (`None` if a file did not exist)
+    originals: Vec<(std::path::PathBuf, Option<String>)>,
+}
+
+impl PendingFiles {
+    /// Content of the file with the changes made so far
+    pub fn content(&self, path: &std::path::Path) -> CmdResult<String> {
+        if let Some(content) = self.contents.get(path) {
+            return Ok(content.clone());
+        }
+        match std::fs::read_to_string(path) {
+            Ok(content) => Ok(content),
+            Err(err) => Err(Error::CannotReadReplacementsFileContent {
+                path: path.to_path_buf(),
+                source: err,
+            }),
+        }
+    }
+
+    pub fn set(&mut self, path: std::path::PathBuf, content: String) {
+        self.contents.insert(path, content);
+    }
+
+    /// Write every file. If one cannot be written, the already written ones are restored
+    pub fn write(&mut self) -> CmdResult {
+        for (path, content) in self.contents.iter() {
+            let original = match std::fs::read_to_string(path) {
+                Ok(original) => Some(original),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+                Err(err) => {
+                    self.restore();
+                    return Err(Error::CannotReadReplacementsFileContent {
+                        path: path.clone(),
+                        source: err,
+                    });
+                }
+            };
+            if let Err(err) = std::fs::write(path, content) {
+                self.restore();
+                return Err(Error::CannotWriteToFile {
+                    path: path.clone(),
+                    source: err,
+                });
+            }
+            self.originals.push((path.clone(), original));
+        }
+        Ok(())
+    }
+
+    /// Put back what `write` has changed when a later step of the transaction fails.
+    /// Nothing else can be done if restoring fails too, so such errors are ignored
+    pub fn restore(&self) {
+        for (path, original) in self.originals.iter().rev() {
+            let _restored = match original {
+                Some(original) => std::fs::write(path, original),
+                None => std::fs::remove_file(path),
+            };
+        }
+    }
 }
 
 pub struct RTContext {
     base_path: std::ffi::OsString,
+    pub output: OutputFormat,
+    /// Path relative to the workspace's root if it's a workspace member
+    member: Option<String>,
+    /// Base path of the workspace's root (the same as `base_path` if it's not a member)
+    root_path: std::ffi::OsString,
+    /// Loaded on first access to a `<member>:<profile>`
+    members: std::cell::OnceCell<Vec<RTContext>>,
 }
 
 // initializing
+impl Default for RTContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RTContext {
+    /// Context of the CLI: the project is in `WEEE_PROJECT_PATH`
+    /// (or the nearest directory with `.weee` starting from the current one),
+    /// messages are printed as text
     pub fn new() -> Self {
+        let base_path = match std::env::var_os("WEEE_PROJECT_PATH") {
+            Some(val) => val,
+            None => Self::discover_base_path(),
+        };
+        Self::with_output(base_path, OutputFormat::Text)
+    }
+
+    /// Same as `new` but parent directories are not searched
+    /// (i.e. to create a new project inside another one)
+    pub fn in_place() -> Self {
+        let base_path = match std::env::var_os("WEEE_PROJECT_PATH") {
+            Some(val) => val,
+            None => std::ffi::OsString::from("."),
+        };
+        Self::with_output(base_path, OutputFormat::Text)
+    }
+
+    /// Context of a project at the path which prints nothing
+    pub fn at<P: Into<std::ffi::OsString>>(base_path: P) -> Self {
+        Self::with_output(base_path.into(), OutputFormat::Silent)
+    }
+
+    fn with_output(base_path: std::ffi::OsString, output: OutputFormat) -> Self {
         RTContext {
-            base_path: match std::env::var_os("WEEE_PROJECT_PATH") {
-                Some(val) => val,
-                None => std::ffi::OsString::from("."),
-            },
+            root_path: base_path.clone(),
+            base_path,
+            output,
+            member: None,
+            members: std::cell::OnceCell::new(),
+        }
+    }
+
+    /// Relative path to the nearest directory with `.weee` (or its workspace)
+    /// so paths in messages stay short. It's `.` if there is none
+    fn discover_base_path() -> std::ffi::OsString {
+        let current_dir = match std::env::current_dir() {
+            Ok(dir) => dir,
+            Err(_err) => return std::ffi::OsString::from("."),
+        };
+        match workspace::discover(&current_dir) {
+            Some(root) if root != current_dir => {
+                let levels = current_dir.components().count() - root.components().count();
+                std::iter::repeat_n("..", levels)
+                    .collect::<std::path::PathBuf>()
+                    .into_os_string()
+            }
+            _ => std::ffi::OsString::from("."),
+        }
+    }
+}
+
+// Output
+impl RTContext {
+    pub fn is_text_output(&self) -> bool {
+        self.output == OutputFormat::Text
+    }
+
+    /// Show a prompt for an interactive input. In JSON mode it goes
+    /// to stderr so stdout contains nothing but the document
+    pub fn prompt(&self, prompt: &str) -> CmdResult {
+        let flushed = if self.is_text_output() {
+            print!("{}", prompt);
+            std::io::stdout().flush()
+        } else {
+            eprint!("{}", prompt);
+            std::io::stderr().flush()
+        };
+        if let Err(err) = flushed {
+            return Err(Error::CannotFlushStdout { source: err });
+        };
+        Ok(())
+    }
+
+    /// Read one line of an answer without the line ending.
+    /// `None` means stdin is closed (i.e. all piped answers are consumed)
+    pub fn read_answer(&self) -> CmdResult<Option<String>> {
+        let mut answer = String::new();
+        match std::io::stdin().read_line(&mut answer) {
+            Ok(0) => Ok(None),
+            Ok(_) => Ok(Some(answer.trim_end_matches(['\n', '\r']).to_string())),
+            Err(err) => Err(Error::CannotReadNewValueFromStdin { source: err }),
         }
     }
+
+    pub fn is_interactive(&self) -> bool {
+        std::io::IsTerminal::is_terminal(&std::io::stdin())
+    }
 }
 
 // Working with files creation/deleting
@@ -51,40 +279,36 @@ impl RTContext {
         let weee_dir_path = std::path::Path::new(&self.base_path).join(".weee");
         if let Err(err) = std::fs::create_dir(&weee_dir_path) {
             return match err.kind() {
-                std::io::ErrorKind::AlreadyExists => show_err!(
-                    [WeeeDirectoryAlreadyExists]
-                    => "Weee tool has been already initialized. It's ready to use"
-                ),
-                _ => show_err!(
-                    [WeeeDirectoryAlreadyExists]
-                    => "An OS error occured while creating a .weee directory",
-                    os_error=err
-                ),
+                std::io::ErrorKind::AlreadyExists => Err(Error::WeeeDirectoryAlreadyExists {
+                    path: weee_dir_path,
+                }),
+                _ => Err(Error::CannotCreateWeeeDirectory {
+                    path: weee_dir_path,
+                    source: err,
+                }),
             };
         }
         Ok(())
     }
 
     pub fn create_weee_profile(&self, name: &str) -> CmdResult {
-        let weee_dir_path = std::path::Path::new(".weee");
+        let weee_dir_path = std::path::Path::new(&self.base_path).join(".weee");
         let rules_path = weee_dir_path.join(format!("{}.version.toml", name));
 
         if rules_path.exists() {
-            return show_err!(
-                [ProfileAlreadyExists]
-                => "Such version profile config already exists",
-                file=rules_path.display()
-            );
+            return Err(Error::ProfileAlreadyExists {
+                profile: name.to_string(),
+                path: rules_path,
+            });
         }
 
         let rules_file = std::fs::File::create(&rules_path);
         if let Err(err) = rules_file {
-            return show_err!(
-                [CannotCreateProfileRule]
-                => "An OS error occured while creating rule for the profile",
-                os_error=err,
-                profile=name
-            );
+            return Err(Error::CannotCreateProfileRule {
+                profile: name.to_string(),
+                path: rules_path,
+                source: err,
+            });
         }
 
         Ok(())
@@ -92,8 +316,160 @@ impl RTContext {
 }
 
 impl RTContext {
-    pub fn fetch_profile_conext(&self, profile: &str) -> CmdResult<ProfileContext> {
-        ProfileContext::load(self, profile)
+    /// OS path of a file from profile's `files` (they are always `/`-separated)
+    pub fn project_file_path(&self, name: &str) -> std::path::PathBuf {
+        let mut path = std::path::PathBuf::from(&self.base_path);
+        for path_part in name.split('/') {
+            path.push(path_part);
+        }
+        path
+    }
+
+    pub fn profile_path(&self, profile: &str) -> std::path::PathBuf {
+        std::path::Path::new(&self.base_path)
+            .join(".weee")
+            .join(format!("{}.version.toml", profile))
+    }
+
+    /// Names of all the profiles in `.weee`, sorted,
+    /// followed by `<member>:<profile>` names of workspace members
+    pub fn profile_names(&self) -> CmdResult<Vec<String>> {
+        let mut names = self.own_profile_names()?;
+        for member in self.members()? {
+            for name in member.own_profile_names()? {
+                names.push(member.qualified_profile_name(&name));
+            }
+        }
+        Ok(names)
+    }
+
+    fn own_profile_names(&self) -> CmdResult<Vec<String>> {
+        let weee_dir_path = std::path::Path::new(&self.base_path).join(".weee");
+        let entries = match std::fs::read_dir(&weee_dir_path) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => {
+                return Err(Error::CannotReadProfile {
+                    profile: "*".into(),
+                    path: weee_dir_path,
+                    source: err,
+                })
+            }
+        };
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let file_name = entry.file_name().to_str()?.to_string();
+                Some(file_name.strip_suffix(".version.toml")?.to_string())
+            })
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Directory of hook logs (see `hooks.save_logs`)
+    pub fn logs_path(&self) -> std::path::PathBuf {
+        std::path::Path::new(&self.base_path)
+            .join(".weee")
+            .join("logs")
+    }
+
+    /// History is kept by the workspace's root, so bumps of members are undone there too
+    pub fn history_path(&self) -> std::path::PathBuf {
+        std::path::Path::new(&self.root_path)
+            .join(".weee")
+            .join("history.jsonl")
+    }
+
+    /// Profiles with patterns like `packages/*:project` replaced by matching profiles
+    /// (sorted, members after the root's profiles). A pattern matching nothing is an error
+    pub fn expand_profile_patterns(&self, profiles: &[String]) -> CmdResult<Vec<String>> {
+        let mut expanded = vec![];
+        let mut names = None;
+        for profile in profiles {
+            if !profile.contains(['*', '?']) {
+                expanded.push(profile.clone());
+                continue;
+            }
+            let names = match &names {
+                Some(names) => names,
+                None => names.insert(self.profile_names()?),
+            };
+            let matched: Vec<&String> = names
+                .iter()
+                .filter(|name| workspace::matches_profile(profile, name))
+                .collect();
+            if matched.is_empty() {
+                return Err(Error::NoProfileMatchesPattern {
+                    pattern: profile.clone(),
+                });
+            }
+            expanded.extend(matched.into_iter().cloned());
+        }
+        Ok(expanded)
+    }
+
+    /// Profiles of a group from `.weee/groups.toml`
+    pub fn profile_group(&self, group: &str) -> CmdResult<Vec<String>> {
+        groups::profiles(std::path::Path::new(&self.base_path), group)
+    }
+
+    pub fn fetch_profile_conext(&self, profile: &str) -> CmdResult<ProfileContext<'_>> {
+        let (ctx, profile) = self.locate_profile(profile)?;
+        ProfileContext::load(ctx, profile)
+    }
+}
+
+// Workspace
+impl RTContext {
+    /// Context of the project the profile belongs to and the profile's name there.
+    /// `<member>:<profile>` refers to a profile of a workspace member
+    pub fn locate_profile<'p>(&self, profile: &'p str) -> CmdResult<(&RTContext, &'p str)> {
+        let (member, name) = match profile.split_once(':') {
+            Some(split) => split,
+            None => return Ok((self, profile)),
+        };
+        let members = self.members()?;
+        match members
+            .iter()
+            .find(|ctx| ctx.member.as_deref() == Some(member))
+        {
+            Some(ctx) => Ok((ctx, name)),
+            None => Err(Error::NoSuchWorkspaceMember {
+                member: member.to_string(),
+                members: members
+                    .iter()
+                    .filter_map(|ctx| ctx.member.clone())
+                    .collect(),
+            }),
+        }
+    }
+
+    /// Name of the project's profile as it's referred to from the workspace's root
+    pub fn qualified_profile_name(&self, profile: &str) -> String {
+        match &self.member {
+            Some(member) => format!("{}:{}", member, profile),
+            None => profile.to_string(),
+        }
+    }
+
+    /// Contexts of the sub-projects listed in `.weee/workspace.toml`.
+    /// Members are never workspaces themselves
+    fn members(&self) -> CmdResult<&[RTContext]> {
+        if let Some(members) = self.members.get() {
+            return Ok(members);
+        }
+        let members = workspace::members(std::path::Path::new(&self.base_path))?
+            .into_iter()
+            .map(|member| RTContext {
+                base_path: self.project_file_path(&member).into_os_string(),
+                output: self.output.clone(),
+                member: Some(member),
+                root_path: self.root_path.clone(),
+                members: std::cell::OnceCell::from(vec![]),
+            })
+            .collect();
+        Ok(self.members.get_or_init(|| members))
     }
 }
 
@@ -105,51 +481,21 @@ pub struct ProfileContext<'rtctx> {
 }
 
 impl<'rtctx> ProfileContext<'rtctx> {
+    /// Profile's own file merged with files it `extends` and `include`s
     pub fn load(rt_context: &'rtctx RTContext, profile: &str) -> CmdResult<Self> {
-        let filename = format!("{}.version.toml", profile);
-        let profile_path = std::path::Path::new(&rt_context.base_path)
-            .join(".weee")
-            .join(filename);
-
-        let profile_content = match std::fs::read_to_string(&profile_path) {
-            Ok(content) => content,
-            Err(err) => {
-                return match err.kind() {
-                    std::io::ErrorKind::NotFound => show_err!(
-                        [NoSuchProfileExists]
-                        => "No such profile exists. Check out whether you typed scope wrongly or deleted the profile file",
-                        profile=profile,
-                        path=match profile_path.to_str() {
-                            Some(val) => val,
-                            None => "<unable to render path>"
-                        }
-                    ),
-                    _ => show_err!(
-                        [CannotCreateProfileRule]
-                        => "An OS error occured while creating rule for the profile",
-                        os_err=err,
-                        profile=profile
-                    ),
-                }
-            }
-        };
-
-        let profile_doc = match profile_content.parse::<Document>() {
+        let composed = composition::compose(rt_context, profile)?;
+        let profile_model = match composed.check() {
             Ok(model) => model,
-            Err(err) => {
-                return show_err!(
-                    [TOMLInvalidSyntax]
-                    => "Invalid syntax in a profile configuration file",
-                    path=profile_path.to_str().unwrap_or("<unable to render path>"),
-                    error=err
-                )
+            Err(diagnostics) => {
+                return Err(Error::InvalidProfileConfig {
+                    profile: profile.to_string(),
+                    path: rt_context.profile_path(profile),
+                    diagnostics,
+                    sources: composed.sources,
+                })
             }
         };
-
-        let profile_model = match toml::from_str::<ProfileConfig>(&profile_content) {
-            Ok(model) => model,
-            Err(_err) => unreachable!("It's a bug, please, report"),
-        };
+        let profile_doc = composed.own;
 
         Ok(ProfileContext {
             rt_context,
@@ -162,13 +508,30 @@ impl<'rtctx> ProfileContext<'rtctx> {
 
 // Checks
 impl<'rtctx> ProfileContext<'rtctx> {
+    /// Name of the profile including its workspace member
+    pub fn qualified_name(&self) -> String {
+        self.rt_context.qualified_profile_name(&self.profile_name)
+    }
+
+    pub fn profile_path(&self) -> std::path::PathBuf {
+        self.rt_context.profile_path(&self.profile_name)
+    }
+
+    pub fn current_version(&self) -> Version {
+        let mut version = LinkedHashMap::new();
+        for (part_name, part_info) in self.profile_model.parts.iter() {
+            version.insert(part_name.clone(), part_info.value.clone());
+        }
+        version
+    }
+
     pub fn check_part_exists(&self, part: &str) -> CmdResult<Part> {
         match self.profile_model.parts.get(part) {
             Some(val) => Ok((*val).clone()),
-            None => show_err!(
-                [NoSuchVersionPartExists]
-                => "Such version part does not exist"
-            ),
+            None => Err(Error::NoSuchVersionPartExists {
+                profile: self.profile_name.clone(),
+                part: part.to_string(),
+            }),
         }
     }
 
@@ -179,11 +542,11 @@ impl<'rtctx> ProfileContext<'rtctx> {
                 Some(payload) => payload.default.unwrap_or_default(),
                 None => 0,
             })),
-            Factory::Loop(chain) => match chain.get(0) {
-                None => show_err!(
-                    [LoopFactoryPayloadIsEmpty]
-                    => "Loop factory payload cannot be empty"
-                ),
+            Factory::Loop(chain) => match chain.first() {
+                None => Err(Error::LoopFactoryPayloadIsEmpty {
+                    profile: self.profile_name.clone(),
+                    part: part.to_string(),
+                }),
                 Some(val) => Ok((*val).clone()),
             },
         }
@@ -195,10 +558,12 @@ impl<'rtctx> ProfileContext<'rtctx> {
             Factory::Increment(_payload) => match existed_part.value {
                 IntegerOrString::Integer(val) => Ok(IntegerOrString::Integer(val + 1)),
                 IntegerOrString::String(val) => match val.parse::<u64>() {
-                    Err(_err) => show_err!(
-                        [CannotParsePartValueToInteger]
-                        => "Version part value is not a valid integer",
-                    ),
+                    Err(err) => Err(Error::CannotParsePartValueToInteger {
+                        profile: self.profile_name.clone(),
+                        part: part.to_string(),
+                        value: val,
+                        source: err,
+                    }),
                     Ok(val) => Ok(IntegerOrString::Integer(val + 1)),
                 },
             },
@@ -211,10 +576,11 @@ impl<'rtctx> ProfileContext<'rtctx> {
                         };
                     }
                 }
-                return show_err!(
-                    [CurrentValueOfLoopedPartDoesNotExist]
-                    => "Version part managed by a loop factory with no such part in payload"
-                );
+                Err(Error::CurrentValueOfLoopedPartDoesNotExist {
+                    profile: self.profile_name.clone(),
+                    part: part.to_string(),
+                    value: existed_part.value.to_string(),
+                })
             }
         }
     }
@@ -223,51 +589,33 @@ impl<'rtctx> ProfileContext<'rtctx> {
         &self,
         version: Version,
         file: &File,
-        file_name: &String,
+        file_name: &str,
     ) -> CmdResult<String> {
-        if file.enable_liquid_tempaltes.unwrap_or_default() {
-            let template = match liquid::ParserBuilder::with_stdlib()
-                .build()
-                .unwrap()
-                .parse(&file.version.view)
-            {
-                Ok(parser) => parser,
-                Err(err) => {
-                    return show_err!(
-                        [InvalidTemplateSyntax]
-                        => "Invalid liquid template syntax",
-                        profile_name=self.profile_name,
-                        file_name=file_name,
-                        template=file.version.view,
-                        error=format!("{}", err)
-
-                    )
-                }
-            };
+        self.render_view(
+            &version,
+            &file.version.view,
+            file.enable_liquid_tempaltes.unwrap_or_default(),
+            file_name,
+        )
+    }
 
+    /// Render a version view such as `{major}.{minor}` (or a liquid template
+    /// if `enable_liquid` is set). `source` is only used in error messages
+    pub fn render_view(
+        &self,
+        version: &Version,
+        view: &str,
+        enable_liquid: bool,
+        source: &str,
+    ) -> CmdResult<String> {
+        if enable_liquid {
             let mut globals = liquid::Object::new();
             for (key, value) in version {
-                globals.insert(
-                    key.into(),
-                    liquid::model::Value::Scalar(match value {
-                        IntegerOrString::Integer(value) => ScalarCow::new(value as i64),
-                        IntegerOrString::String(value) => ScalarCow::new(value.clone()),
-                    }),
-                );
-            }
-            match template.render(&globals) {
-                Ok(output) => Ok(output),
-                Err(err) => show_err!(
-                    [LiquidTemplateRuntimeError]
-                    => "An error occured while rendering a template",
-                    profile_name=self.profile_name,
-                    file_name=file_name,
-                    template=file.version.view,
-                    error=err
-                ),
+                globals.insert(key.clone().into(), part_to_liquid(value));
             }
+            self.render_liquid(view, &globals, source)
         } else {
-            let mut new_string = file.version.view.clone();
+            let mut new_string = view.to_string();
             for (version_part, version_value) in version.iter() {
                 let temp_val_string;
                 new_string = new_string.replace(
@@ -285,18 +633,76 @@ impl<'rtctx> ProfileContext<'rtctx> {
         }
     }
 
+    fn render_liquid(
+        &self,
+        template: &str,
+        globals: &liquid::Object,
+        source: &str,
+    ) -> CmdResult<String> {
+        let parsed_template = match liquid::ParserBuilder::with_stdlib()
+            .build()
+            .unwrap()
+            .parse(template)
+        {
+            Ok(parser) => parser,
+            Err(err) => {
+                return Err(Error::InvalidTemplateSyntax {
+                    profile: self.profile_name.clone(),
+                    origin: source.to_string(),
+                    template: template.to_string(),
+                    source: err,
+                })
+            }
+        };
+
+        match parsed_template.render(globals) {
+            Ok(output) => Ok(output),
+            Err(err) => Err(Error::LiquidTemplateRuntimeError {
+                profile: self.profile_name.clone(),
+                origin: source.to_string(),
+                template: template.to_string(),
+                source: err,
+            }),
+        }
+    }
+
     pub fn prepare_replacemts(
         &self,
         changed_version: &ChangedVersion,
+    ) -> CmdResult<Vec<ChangedFile>> {
+        self.prepare_files_replacements(&self.profile_model.files, changed_version)
+    }
+
+    /// Replacements of references to a dependency's version
+    pub fn prepare_dependency_replacements(
+        &self,
+        dependency: &str,
+        changed_version: &ChangedVersion,
+    ) -> CmdResult<Vec<ChangedFile>> {
+        match self
+            .profile_model
+            .dependencies
+            .as_ref()
+            .and_then(|dependencies| dependencies.get(dependency))
+        {
+            Some(dependency) => self.prepare_files_replacements(&dependency.files, changed_version),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn prepare_files_replacements(
+        &self,
+        files: &LinkedHashMap<String, Vec<File>>,
+        changed_version: &ChangedVersion,
     ) -> CmdResult<Vec<ChangedFile>> {
         let mut changed_files = vec![];
 
-        for (file_name, file_replacements) in self.profile_model.files.iter() {
+        for (file_name, file_replacements) in files.iter() {
             for file_replacement in file_replacements.iter() {
                 let old_version = self.insert_version_into_string(
                     changed_version.old.clone(),
-                    &file_replacement,
-                    &file_name,
+                    file_replacement,
+                    file_name,
                 )?;
                 let old_part = file_replacement
                     .version
@@ -304,8 +710,8 @@ impl<'rtctx> ProfileContext<'rtctx> {
                     .replace("{version}", old_version.as_str());
                 let new_version = self.insert_version_into_string(
                     changed_version.new.clone(),
-                    &file_replacement,
-                    &file_name,
+                    file_replacement,
+                    file_name,
                 )?;
                 let new_part = file_replacement
                     .version
@@ -318,6 +724,7 @@ impl<'rtctx> ProfileContext<'rtctx> {
                     new_part,
                     new_version,
                     old_version,
+                    replaces_count: file_replacement.replaces_count,
                 });
             }
         }
@@ -328,12 +735,7 @@ impl<'rtctx> ProfileContext<'rtctx> {
         let part_info = self.check_part_exists(requested_part)?;
 
         let mut new_version = LinkedHashMap::new();
-        let mut old_version = LinkedHashMap::new();
-
-        // Collect old version
-        for (part_name, part_info) in self.profile_model.parts.iter() {
-            old_version.insert(part_name.clone(), part_info.value.clone());
-        }
+        let old_version = self.current_version();
 
         // Collect new version
         match part_info.factory {
@@ -382,6 +784,12 @@ impl<'rtctx> ProfileContext<'rtctx> {
                 }
             }
         }
+
+        // Loop factory collects parts in reversed order, so restore the profile's one
+        let new_version = old_version
+            .keys()
+            .map(|part_name| (part_name.clone(), new_version[part_name].clone()))
+            .collect();
         Ok(ChangedVersion {
             new: new_version,
             old: old_version,
@@ -390,103 +798,93 @@ impl<'rtctx> ProfileContext<'rtctx> {
 
     pub fn change_files_content(
         &self,
-        changed_files: &Vec<ChangedFile>,
+        changed_files: &[ChangedFile],
         read_only: bool,
-    ) -> CmdResult {
-        // One file can have multiply patterns, but this function accepts
-        // a vector of changes (i.e. applied pattern changing)
-        // So if we count "hits" of filename we can detect
-        // which pattern of given is now
-        let mut filenames_hits: std::collections::HashMap<&String, usize> =
-            std::collections::HashMap::new();
+    ) -> CmdResult<Vec<FileReport>> {
+        // Files are written only when all of the replacements succeeded
+        // so a failed pattern never leaves the project half-bumped
+        let mut pending = PendingFiles::default();
+        let reports = self.replace_in_files(changed_files, &mut pending)?;
+        if !read_only {
+            pending.write()?;
+        }
+        Ok(reports)
+    }
 
-        // If a file has beed changed by abother pattern, we should keep new changes
-        let mut changed_files_content: std::collections::HashMap<&String, String> =
-            std::collections::HashMap::new();
+    /// Apply replacements to the pending contents of files
+    pub fn replace_in_files(
+        &self,
+        changed_files: &[ChangedFile],
+        pending: &mut PendingFiles,
+    ) -> CmdResult<Vec<FileReport>> {
+        let mut reports = vec![];
 
         for file in changed_files.iter() {
-            let splited_path: Vec<&str> = file.name.split("/").collect();
-            let mut os_based_file_path =
-                std::path::Path::new(&self.rt_context.base_path).join(splited_path[0]);
-            for path_part in &splited_path[1..] {
-                os_based_file_path = os_based_file_path.join(path_part);
-            }
+            let os_based_file_path = self.rt_context.project_file_path(&file.name);
 
             if !os_based_file_path.exists() {
-                return show_err!(
-                    [NoSuchFileForReplacements]
-                    => "No such file to make version replacements"
-                );
+                return Err(Error::NoSuchFileForReplacements {
+                    profile: self.profile_name.clone(),
+                    path: os_based_file_path,
+                });
             }
-            let file_content_string;
-            let file_content = match &changed_files_content.get(&file.name) {
-                Some(content) => content,
-                None => match std::fs::read_to_string(&os_based_file_path) {
-                    Ok(content) => {
-                        file_content_string = content;
-                        &file_content_string
-                    }
-                    Err(_err) => {
-                        return show_err!(
-                            [CannotReadReplacementsFileContent]
-                            => "Cannot read file to make replacements"
-                        )
-                    }
-                },
-            };
+            // If a file has beed changed by abother pattern, we should keep new changes
+            let file_content = pending.content(&os_based_file_path)?;
 
             let old_version_matches_count = file_content.matches(&file.old_part).count() as u64;
 
-            let this_file_paterns = &self.profile_model.files[&file.name];
-            let current_hits = filenames_hits.entry(&file.name).or_insert(0);
-
+            let replaced_count;
             let new_file_content;
-            if let Some(replaces_count) = this_file_paterns[*current_hits].replaces_count {
+            if let Some(replaces_count) = file.replaces_count {
                 if replaces_count < old_version_matches_count {
-                    return show_err!(
-                        [NotEnoughOldVersionMatches]
-                        => "Count of old version entries is not as supposed"
-                    );
+                    return Err(Error::NotEnoughOldVersionMatches {
+                        profile: self.profile_name.clone(),
+                        file: file.name.clone(),
+                        old_match: file.old_part.clone(),
+                        expected: replaces_count,
+                        found: old_version_matches_count,
+                    });
                 }
+                replaced_count = replaces_count as usize;
                 new_file_content =
-                    file_content.replacen(&file.old_part, &file.new_part, replaces_count as usize);
+                    file_content.replacen(&file.old_part, &file.new_part, replaced_count);
             } else if old_version_matches_count == 0 {
-                return show_err!(
-                    [FileDoesNotContainOldVersion]
-                    => "Changed files has no old version in it's content",
-                    profile=self.profile_name,
-                    file=file.name,
-                    old_match=&file.old_part,
-
-                );
+                return Err(Error::FileDoesNotContainOldVersion {
+                    profile: self.profile_name.clone(),
+                    file: file.name.clone(),
+                    old_match: file.old_part.clone(),
+                });
             } else {
+                replaced_count = old_version_matches_count as usize;
                 new_file_content = file_content.replace(&file.old_part, &file.new_part);
             }
+            let lines = file_content
+                .match_indices(&file.old_part)
+                .take(replaced_count)
+                .map(|(index, _)| file_content[..index].matches('\n').count() + 1)
+                .collect();
+            pending.set(os_based_file_path.clone(), new_file_content);
 
-            if !read_only {
-                if let Err(_err) = std::fs::write(&os_based_file_path, &new_file_content) {
-                    return show_err!(
-                        [CannotWriteToFile]
-                        => "Cannot write new version into file"
-                    );
-                };
+            if self.rt_context.is_text_output() {
+                println!(
+                    "[{}]: {} => {}",
+                    os_based_file_path
+                        .to_str()
+                        .unwrap_or("<cannot render path>")
+                        .magenta(),
+                    file.old_version.red(),
+                    file.new_version.green(),
+                );
             }
-
-            changed_files_content.insert(&file.name, new_file_content);
-
-            println!(
-                "[{}]: {} => {}",
-                os_based_file_path
-                    .to_str()
-                    .unwrap_or("<cannot render path>")
-                    .magenta(),
-                file.old_version.red(),
-                file.new_version.green(),
-            );
-            *current_hits += 1;
+            reports.push(FileReport {
+                path: file.name.clone(),
+                old_version: file.old_version.clone(),
+                new_version: file.new_version.clone(),
+                lines,
+            });
         }
 
-        Ok(())
+        Ok(reports)
     }
 
     pub fn update_storage(
@@ -494,6 +892,22 @@ impl<'rtctx> ProfileContext<'rtctx> {
         changed_version: &ChangedVersion,
         read_only: bool,
     ) -> CmdResult {
+        self.set_storage(changed_version);
+        if !read_only {
+            let profile_path = self.profile_path();
+            if let Err(err) = std::fs::write(&profile_path, self.profile_doc.to_string()) {
+                return Err(Error::CannotWriteToProfileFile {
+                    profile: self.profile_name.clone(),
+                    path: profile_path,
+                    source: err,
+                });
+            };
+        }
+        Ok(())
+    }
+
+    /// Put new values of parts into the profile's document without writing it
+    fn set_storage(&mut self, changed_version: &ChangedVersion) {
         for (part, new_value) in changed_version.new.iter() {
             match new_value {
                 IntegerOrString::Integer(val) => {
@@ -504,24 +918,19 @@ impl<'rtctx> ProfileContext<'rtctx> {
                 }
             };
         }
+    }
 
-        if !read_only {
-            if let Err(_err) = std::fs::write(
-                std::path::Path::new(&self.rt_context.base_path)
-                    .join(".weee")
-                    .join(format!("{}.version.toml", self.profile_name)),
-                self.profile_doc.to_string(),
-            ) {
-                return show_err!(
-                    [CannotWriteToProfileFile]
-                    => "An OS error accured while writing to profile file"
-                );
-            };
+    /// Render the version with the first file's view
+    /// (or join parts with dots if the profile has no files)
+    pub fn render_main_view(&self, version: &Version) -> CmdResult<String> {
+        match self.profile_model.files.iter().next() {
+            Some((file_name, views)) if !views.is_empty() => {
+                self.insert_version_into_string(version.clone(), &views[0], file_name)
+            }
+            _ => Ok(self.version_to_string(version)),
         }
-        Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn version_to_string(&self, version: &Version) -> String {
         let mut result_string = String::new();
         for (ind, pair) in version.iter().enumerate() {
@@ -540,54 +949,220 @@ impl<'rtctx> ProfileContext<'rtctx> {
     }
 }
 
+/// Piece of a version view: either a literal text or a `{part}` placeholder
+enum ViewToken<'a> {
+    Literal(&'a str),
+    Part(&'a str),
+}
+
+// Parsing versions back from strings
 impl<'rtctx> ProfileContext<'rtctx> {
-    fn show_tip(&self, prompt: &'static str) {
-        eprintln!(" \u{1F4A5} Oh! {}", prompt);
+    /// Parse a rendered version (i.e. a git tag) back into parts using
+    /// the first file's view. Parts missing in the view get their defaults.
+    /// Returns `None` if the string does not match the view
+    pub fn parse_main_view(&self, rendered: &str) -> CmdResult<Option<Version>> {
+        let (file_name, file) = match self.profile_model.files.iter().next() {
+            Some((file_name, views)) if !views.is_empty() => (file_name, &views[0]),
+            _ => {
+                return self.parse_view(
+                    &self
+                        .profile_model
+                        .parts
+                        .keys()
+                        .map(|part| format!("{{{}}}", part))
+                        .collect::<Vec<_>>()
+                        .join("."),
+                    rendered,
+                )
+            }
+        };
+        if file.enable_liquid_tempaltes.unwrap_or_default() {
+            return Err(Error::ViewCannotBeParsed {
+                profile: self.profile_name.clone(),
+                file: file_name.to_string(),
+                view: file.version.view.clone(),
+            });
+        }
+        self.parse_view(&file.version.view, rendered)
     }
 
-    pub fn ask_another_version(&self) -> CmdResult<Version> {
-        let mut new_version = LinkedHashMap::new();
-        for (part_name, part_info) in self.profile_model.parts.iter() {
-            new_version.insert(
-                part_name.clone(),
-                self.ask_for_part(&part_name, &part_info)?,
-            );
+    /// Match `rendered` against a view like `{major}.{minor}{stage}`.
+    /// Increment parts match digits, loop parts match one of the payload's values
+    pub fn parse_view(&self, view: &str, rendered: &str) -> CmdResult<Option<Version>> {
+        let tokens = self.tokenize_view(view);
+        let mut found = LinkedHashMap::new();
+        if !self.match_tokens(&tokens, rendered, &mut found) {
+            return Ok(None);
         }
 
-        Ok(new_version)
+        let mut version = LinkedHashMap::new();
+        for part_name in self.profile_model.parts.keys() {
+            let value = match found.remove(part_name.as_str()) {
+                Some(value) => value,
+                None => self.fetch_default_of_part(part_name)?,
+            };
+            version.insert(part_name.clone(), value);
+        }
+        Ok(Some(version))
     }
 
-    fn ask_for_part(&self, part_name: &str, part_info: &Part) -> CmdResult<IntegerOrString<u64>> {
-        loop {
-            let mut new_part_value = String::new();
-            print!(
-                "[{} {}]: ",
-                part_name.magenta(),
-                format!(
-                    "({})",
-                    match &part_info.value {
-                        IntegerOrString::Integer(val) => val.to_string(),
-                        IntegerOrString::String(val) => val.to_string(),
+    fn tokenize_view<'v>(&self, view: &'v str) -> Vec<ViewToken<'v>> {
+        let mut tokens = vec![];
+        let mut rest = view;
+        let mut literal_start = 0;
+        while let Some(open) = rest[literal_start..].find('{') {
+            let open = literal_start + open;
+            let part = rest[open + 1..]
+                .find('}')
+                .map(|close| &rest[open + 1..open + 1 + close])
+                .filter(|part| self.profile_model.parts.contains_key(*part));
+            match part {
+                Some(part) => {
+                    if open > 0 {
+                        tokens.push(ViewToken::Literal(&rest[..open]));
                     }
-                    .yellow()
-                )
-                .bright_black()
-            );
-            if let Err(_err) = std::io::stdout().flush() {
-                return show_err!(
-                    [CannotFlushStdout]
-                    => "Cannot flush stdout"
-                );
-            };
-            if let Err(_err) = std::io::stdin().read_line(&mut new_part_value) {
-                return show_err!(
-                    [CannotReadNewValueFromStdin]
-                    => "Cannot get an input for new part value"
-                );
+                    tokens.push(ViewToken::Part(part));
+                    rest = &rest[open + part.len() + 2..];
+                    literal_start = 0;
+                }
+                // Not a placeholder, so it's a part of the literal
+                None => literal_start = open + 1,
             }
-            new_part_value.truncate(new_part_value.len() - 1);
+        }
+        if !rest.is_empty() {
+            tokens.push(ViewToken::Literal(rest));
+        }
+        tokens
+    }
 
-            match &part_info.factory {
+    /// Backtracking matcher: views are short, so it's cheap
+    fn match_tokens<'v>(
+        &self,
+        tokens: &[ViewToken<'v>],
+        rendered: &str,
+        found: &mut LinkedHashMap<&'v str, IntegerOrString<u64>>,
+    ) -> bool {
+        let (token, rest_tokens) = match tokens.split_first() {
+            Some(pair) => pair,
+            None => return rendered.is_empty(),
+        };
+        let part = match token {
+            ViewToken::Literal(literal) => {
+                return match rendered.strip_prefix(literal) {
+                    Some(rest) => self.match_tokens(rest_tokens, rest, found),
+                    None => false,
+                }
+            }
+            ViewToken::Part(part) => *part,
+        };
+
+        let mut candidates: Vec<(usize, IntegerOrString<u64>)> = vec![];
+        match &self.profile_model.parts[part].factory {
+            Factory::Increment(_payload) => {
+                let digits = rendered
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rendered.len());
+                // Longest numbers first
+                for len in (1..=digits).rev() {
+                    if let Ok(value) = rendered[..len].parse::<u64>() {
+                        candidates.push((len, IntegerOrString::Integer(value)));
+                    }
+                }
+            }
+            Factory::Loop(chain) => {
+                for value in chain {
+                    let value_string = value.to_string();
+                    if rendered.starts_with(&value_string) {
+                        candidates.push((value_string.len(), value.clone()));
+                    }
+                }
+                candidates.sort_by_key(|(len, _value)| std::cmp::Reverse(*len));
+            }
+        }
+
+        for (len, value) in candidates {
+            // The same part may be used in a view twice, so values have to agree
+            let previous = found.get(part).cloned();
+            if previous.as_ref().is_some_and(|previous| *previous != value) {
+                continue;
+            }
+            found.insert(part, value);
+            if self.match_tokens(rest_tokens, &rendered[len..], found) {
+                return true;
+            }
+            match previous {
+                Some(previous) => found.insert(part, previous),
+                None => found.remove(part),
+            };
+        }
+        false
+    }
+
+    /// Compare versions part by part in the profile's order.
+    /// Loop parts are ordered by their position in the payload
+    pub fn compare_versions(&self, left: &Version, right: &Version) -> std::cmp::Ordering {
+        for (part_name, part_info) in self.profile_model.parts.iter() {
+            let rank = |version: &Version| -> Option<u64> {
+                let value = version.get(part_name)?;
+                match (&part_info.factory, value) {
+                    (Factory::Loop(chain), value) => chain
+                        .iter()
+                        .position(|elem| elem == value)
+                        .map(|pos| pos as u64),
+                    (Factory::Increment(_payload), IntegerOrString::Integer(val)) => Some(*val),
+                    (Factory::Increment(_payload), IntegerOrString::String(val)) => {
+                        val.parse().ok()
+                    }
+                }
+            };
+            let ordering = rank(left).cmp(&rank(right));
+            if ordering != std::cmp::Ordering::Equal {
+                return ordering;
+            }
+        }
+        std::cmp::Ordering::Equal
+    }
+}
+
+impl<'rtctx> ProfileContext<'rtctx> {
+    fn show_tip(&self, prompt: &'static str) {
+        eprintln!(" \u{1F4A5} Oh! {}", prompt);
+    }
+
+    pub fn ask_another_version(&self) -> CmdResult<Version> {
+        let mut new_version = LinkedHashMap::new();
+        for (part_name, part_info) in self.profile_model.parts.iter() {
+            new_version.insert(part_name.clone(), self.ask_for_part(part_name, part_info)?);
+        }
+
+        Ok(new_version)
+    }
+
+    fn ask_for_part(&self, part_name: &str, part_info: &Part) -> CmdResult<IntegerOrString<u64>> {
+        loop {
+            self.rt_context.prompt(&format!(
+                "[{} {}]: ",
+                part_name.magenta(),
+                format!(
+                    "({})",
+                    match &part_info.value {
+                        IntegerOrString::Integer(val) => val.to_string(),
+                        IntegerOrString::String(val) => val.to_string(),
+                    }
+                    .yellow()
+                )
+                .bright_black()
+            ))?;
+            let new_part_value = match self.rt_context.read_answer()? {
+                Some(value) => value,
+                None => {
+                    return Err(Error::InputIsNotAvailable {
+                        prompt: part_name.to_string(),
+                    })
+                }
+            };
+
+            match &part_info.factory {
                 Factory::Increment(_payload) => {
                     match new_part_value.parse::<u64>() {
                         Ok(value) => return Ok(
@@ -626,105 +1201,1524 @@ impl<'rtctx> ProfileContext<'rtctx> {
 
 // hooks
 impl<'rtctx> ProfileContext<'rtctx> {
-    fn process_args(
-        &self,
-        args: &Vec<String>,
-        changed_version: &ChangedVersion,
-    ) -> CmdResult<Vec<String>> {
+    /// `!ASK:<prompt>|<default>`. When stdin is not a terminal, the answer is still
+    /// read from it line by line, and the default is used once stdin is closed
+    fn ask_arg(&self, prompt: &str) -> CmdResult<String> {
+        let (prompt, default) = split_default(prompt);
+        match default {
+            Some(default) => self.rt_context.prompt(&format!(
+                "===> [{}] {}: ",
+                prompt.magenta(),
+                format!("({})", default.yellow()).bright_black()
+            ))?,
+            None => self
+                .rt_context
+                .prompt(&format!("===> [{}]: ", prompt.magenta()))?,
+        };
+
+        match (self.rt_context.read_answer()?, default) {
+            (Some(answer), Some(default)) if answer.is_empty() => Ok(default.to_string()),
+            (Some(answer), _) => Ok(answer),
+            (None, Some(default)) => Ok(default.to_string()),
+            (None, None) => Err(Error::InputIsNotAvailable {
+                prompt: prompt.to_string(),
+            }),
+        }
+    }
+
+    /// `!EDITOR:<prompt>|<default>` opens `$VISUAL` or `$EDITOR` (`vi` if none of them is set)
+    /// on a temporary file. Lines starting with `#` are removed from the result.
+    /// Without a terminal the default is used
+    fn edit_arg(&self, prompt: &str) -> CmdResult<String> {
+        let (prompt, default) = split_default(prompt);
+        if !self.rt_context.is_interactive() {
+            return match default {
+                Some(default) => Ok(default.to_string()),
+                None => Err(Error::InputIsNotAvailable {
+                    prompt: prompt.to_string(),
+                }),
+            };
+        }
+
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| "vi".to_string());
+        let initial_content = format!(
+            "{}\n# {}\n# Lines starting with '#' will be ignored\n",
+            default.unwrap_or_default(),
+            prompt
+        );
+        // A new file with a random name, so nobody can prepare it in advance.
+        // It's removed when dropped
+        let temp_file = tempfile::Builder::new()
+            .prefix("weee-")
+            .suffix("-edit.txt")
+            .tempfile()
+            .and_then(|mut temp_file| {
+                temp_file.write_all(initial_content.as_bytes())?;
+                Ok(temp_file)
+            });
+        let temp_file = match temp_file {
+            Ok(temp_file) => temp_file,
+            Err(err) => {
+                return Err(Error::CannotRunEditor {
+                    editor,
+                    path: std::env::temp_dir(),
+                    status: None,
+                    source: Some(err),
+                })
+            }
+        };
+        let temp_path = temp_file.path().to_path_buf();
+
+        let mut editor_args = editor.split_whitespace();
+        let status = std::process::Command::new(editor_args.next().unwrap_or("vi"))
+            .args(editor_args)
+            .arg(&temp_path)
+            .status();
+        let content = std::fs::read_to_string(&temp_path);
+        drop(temp_file);
+
+        match (status, content) {
+            (Ok(status), Ok(content)) if status.success() => Ok(content
+                .lines()
+                .filter(|line| !line.starts_with('#'))
+                .collect::<Vec<_>>()
+                .join("\n")
+                .trim()
+                .to_string()),
+            (Ok(status), content) => Err(Error::CannotRunEditor {
+                editor,
+                path: temp_path,
+                status: Some(status),
+                source: content.err(),
+            }),
+            (Err(err), _) => Err(Error::CannotRunEditor {
+                editor,
+                path: temp_path,
+                status: None,
+                source: Some(err),
+            }),
+        }
+    }
+
+    /// `!ENV:<name>|<default>`
+    fn env_arg(&self, variable: &str) -> CmdResult<String> {
+        let (variable, default) = split_default(variable);
+        match (std::env::var(variable), default) {
+            (Ok(value), _) => Ok(value),
+            (Err(_err), Some(default)) => Ok(default.to_string()),
+            (Err(err), None) => Err(Error::NoSuchEnvironmentVariable {
+                variable: variable.to_string(),
+                source: err,
+            }),
+        }
+    }
+
+    /// `!FILE:<path>` relative to the project's root. A trailing line ending is removed
+    fn file_arg(&self, path: &str) -> CmdResult<String> {
+        let file_path = std::path::Path::new(&self.rt_context.base_path).join(path);
+        match std::fs::read_to_string(&file_path) {
+            Ok(content) => Ok(content
+                .strip_suffix('\n')
+                .map(|content| content.strip_suffix('\r').unwrap_or(content))
+                .unwrap_or(&content)
+                .to_string()),
+            Err(err) => Err(Error::CannotReadArgumentFile {
+                path: file_path,
+                source: err,
+            }),
+        }
+    }
+
+    /// Render `!FORMAT:` argument. Liquid is used if the argument has any of its tags,
+    /// otherwise `{old.<part>}`/`{new.<part>}` are simply replaced
+    fn format_arg(&self, formatable: &str, scope: &HookScope) -> CmdResult<String> {
+        if formatable.contains("{{") || formatable.contains("{%") {
+            return self.render_liquid(formatable, &self.hook_globals(scope)?, "<hook argument>");
+        }
+
+        let mut new_value = formatable.to_string();
+        for (part, value) in scope.changed_version.old.iter() {
+            new_value = new_value.replace(
+                format!("{{old.{}}}", part).as_str(),
+                value.to_string().as_str(),
+            )
+        }
+        for (part, value) in scope.changed_version.new.iter() {
+            new_value = new_value.replace(
+                format!("{{new.{}}}", part).as_str(),
+                value.to_string().as_str(),
+            )
+        }
+        Ok(new_value)
+    }
+
+    fn process_args(&self, args: &[String], scope: &HookScope) -> CmdResult<Vec<String>> {
         let mut new_args = vec![];
-        for (_ind, arg) in args.iter().enumerate() {
-            if arg.starts_with("!ASK:") {
-                let split_data = arg.split_once(":");
-                if let Some((_, prompt)) = split_data {
-                    print!("===> [{}]: ", prompt.magenta());
-                    if let Err(_err) = std::io::stdout().flush() {
-                        return show_err!(
-                            [CannotFlushStdout]
-                            => "Cannot flush stdout"
-                        );
-                    };
-                    let mut new_value = String::new();
-                    if let Err(_err) = std::io::stdin().read_line(&mut new_value) {
-                        return show_err!(
-                            [CannotReadNewValueFromStdin]
-                            => "Cannot get an input for asked value"
+        for arg in args.iter() {
+            if let Some(prompt) = arg.strip_prefix("!ASK:") {
+                new_args.push(self.ask_arg(prompt)?);
+            } else if let Some(prompt) = arg.strip_prefix("!EDITOR:") {
+                new_args.push(self.edit_arg(prompt)?);
+            } else if let Some(variable) = arg.strip_prefix("!ENV:") {
+                new_args.push(self.env_arg(variable)?);
+            } else if let Some(path) = arg.strip_prefix("!FILE:") {
+                new_args.push(self.file_arg(path)?);
+            } else if let Some(formatable) = arg.strip_prefix("!FORMAT:") {
+                new_args.push(self.format_arg(formatable, scope)?);
+            } else {
+                new_args.push(arg.clone());
+            }
+        }
+        Ok(new_args)
+    }
+
+    pub fn execute_before_hooks(&self, scope: &HookScope) -> CmdResult<Vec<HookReport>> {
+        self.execute_stage_hooks(HookStage::Before, scope, &mut ExecutedHooks::default())
+    }
+
+    pub fn execute_afterword_hooks(&self, scope: &HookScope) -> CmdResult<Vec<HookReport>> {
+        self.execute_stage_hooks(HookStage::Afterwords, scope, &mut ExecutedHooks::default())
+    }
+
+    pub fn execute_failure_hooks(&self, scope: &HookScope) -> CmdResult<Vec<HookReport>> {
+        self.execute_stage_hooks(HookStage::OnFailure, scope, &mut ExecutedHooks::default())
+    }
+
+    /// Execute hooks of the stage except the ones already executed
+    /// for another profile of the same transaction
+    pub fn execute_stage_hooks(
+        &self,
+        stage: HookStage,
+        scope: &HookScope,
+        executed: &mut ExecutedHooks,
+    ) -> CmdResult<Vec<HookReport>> {
+        let hooks = self
+            .profile_model
+            .hooks
+            .as_ref()
+            .and_then(|hooks| match stage {
+                HookStage::Before => hooks.before.as_ref(),
+                HookStage::Afterwords => hooks.afterwords.as_ref(),
+                HookStage::OnFailure => hooks.on_failure.as_ref(),
+            });
+        self.execute_hooks(stage, hooks, scope, executed)
+    }
+
+    /// Reason why the hook should not be executed in this scope (if any)
+    fn hook_skip_reason(&self, hook: &Hook, scope: &HookScope) -> CmdResult<Option<String>> {
+        let hook = match hook {
+            Hook::Args(_) => return Ok(None),
+            Hook::Detailed(hook) => hook,
+        };
+        if scope.read_only && hook.skip_in_read_only.unwrap_or_default() {
+            return Ok(Some("read-only mode".into()));
+        }
+        if let Some(only_profiles) = &hook.only_profiles {
+            if !only_profiles.contains(&self.profile_name) {
+                return Ok(Some(format!("profile is not one of {:?}", only_profiles)));
+            }
+        }
+        if let Some(only_parts) = &hook.only_parts {
+            match scope.bumped_part {
+                Some(part) if only_parts.iter().any(|name| name == part) => {}
+                _ => return Ok(Some(format!("bumped part is not one of {:?}", only_parts))),
+            }
+        }
+        if let Some(when) = &hook.when {
+            let rendered = self.render_liquid(
+                &when_template(when),
+                &self.hook_globals(scope)?,
+                "<hook when>",
+            )?;
+            if rendered.trim() != "true" {
+                return Ok(Some(format!("condition `{}` is false", when)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Objects available in hooks' liquid templates
+    fn hook_globals(&self, scope: &HookScope) -> CmdResult<liquid::Object> {
+        let mut globals = liquid::Object::new();
+        globals.insert("old".into(), version_to_liquid(&scope.changed_version.old));
+        globals.insert("new".into(), version_to_liquid(&scope.changed_version.new));
+        globals.insert(
+            "old_version".into(),
+            liquid::model::Value::scalar(self.render_main_view(&scope.changed_version.old)?),
+        );
+        globals.insert(
+            "new_version".into(),
+            liquid::model::Value::scalar(self.render_main_view(&scope.changed_version.new)?),
+        );
+        globals.insert(
+            "profile".into(),
+            liquid::model::Value::scalar(self.profile_name.clone()),
+        );
+        globals.insert(
+            "bumped_part".into(),
+            match scope.bumped_part {
+                Some(part) => liquid::model::Value::scalar(part.to_string()),
+                None => liquid::model::Value::Nil,
+            },
+        );
+        globals.insert(
+            "read_only".into(),
+            liquid::model::Value::scalar(scope.read_only),
+        );
+
+        let mut env = liquid::Object::new();
+        for (key, value) in std::env::vars() {
+            env.insert(key.into(), liquid::model::Value::scalar(value));
+        }
+        globals.insert("env".into(), liquid::model::Value::Object(env));
+
+        // UTC date, use `{{ date.now | date: "%Y-%m-%d" }}` for custom formats
+        let now = liquid::model::DateTime::now();
+        let mut date = liquid::Object::new();
+        date.insert("now".into(), liquid::model::Value::scalar(now));
+        date.insert(
+            "year".into(),
+            liquid::model::Value::scalar(now.year() as i64),
+        );
+        date.insert(
+            "month".into(),
+            liquid::model::Value::scalar(now.month() as i64),
+        );
+        date.insert("day".into(), liquid::model::Value::scalar(now.day() as i64));
+        date.insert(
+            "iso".into(),
+            liquid::model::Value::scalar(format!(
+                "{:04}-{:02}-{:02}",
+                now.year(),
+                now.month(),
+                now.day()
+            )),
+        );
+        globals.insert("date".into(), liquid::model::Value::Object(date));
+        Ok(globals)
+    }
+
+    /// Environment variables describing the bump for hook processes.
+    /// Full versions are rendered with the first file's view
+    fn hook_env(&self, scope: &HookScope) -> CmdResult<Vec<(String, String)>> {
+        let render = |version: &Version| self.render_main_view(version);
+
+        let mut env = vec![
+            ("WEEE_PROFILE".to_string(), self.profile_name.clone()),
+            (
+                "WEEE_OLD_VERSION".to_string(),
+                render(&scope.changed_version.old)?,
+            ),
+            (
+                "WEEE_NEW_VERSION".to_string(),
+                render(&scope.changed_version.new)?,
+            ),
+            (
+                "WEEE_READ_ONLY".to_string(),
+                if scope.read_only { "1" } else { "0" }.to_string(),
+            ),
+        ];
+        if let Some(part) = scope.bumped_part {
+            env.push(("WEEE_BUMPED_PART".to_string(), part.to_string()));
+        }
+        for (prefix, version) in [
+            ("WEEE_OLD_", &scope.changed_version.old),
+            ("WEEE_NEW_", &scope.changed_version.new),
+        ] {
+            for (part, value) in version.iter() {
+                env.push((format!("{}{}", prefix, env_name(part)), value.to_string()));
+            }
+        }
+        Ok(env)
+    }
+
+    fn execute_hooks(
+        &self,
+        stage: HookStage,
+        hooks: Option<&LinkedHashMap<String, Hook>>,
+        scope: &HookScope,
+        executed: &mut ExecutedHooks,
+    ) -> CmdResult<Vec<HookReport>> {
+        let text_output = self.rt_context.is_text_output();
+        let mut reports = vec![];
+        if let Some(hooks) = hooks {
+            if text_output {
+                println!("\n \u{1F50D} Founded some {} hooks...", stage.name());
+            }
+            let env = self.hook_env(scope)?;
+            for (cmd_name, hook) in hooks {
+                if executed.contains(stage, cmd_name, hook) {
+                    if text_output {
+                        println!(
+                            "=> Skipping: {} (already executed for another profile)",
+                            cmd_name.cyan()
                         );
                     }
-                    new_value.truncate(new_value.len() - 1);
-                    new_args.push(new_value);
-                } else {
-                    new_args.push(arg.clone());
+                    continue;
                 }
-            } else if arg.starts_with("!FORMAT:") {
-                let split_data = arg.split_once(":");
-                if let Some((_, formatable)) = split_data {
-                    let mut new_value = formatable.to_string();
-                    for (part, value) in changed_version.old.iter() {
-                        new_value = new_value.replace(
-                            format!("{{old.{}}}", part).as_str(),
-                            value.to_string().as_str(),
-                        )
+                if let Some(reason) = self.hook_skip_reason(hook, scope)? {
+                    if text_output {
+                        println!("=> Skipping: {} ({})", cmd_name.cyan(), reason);
                     }
-                    for (part, value) in changed_version.new.iter() {
-                        new_value = new_value.replace(
-                            format!("{{new.{}}}", part).as_str(),
-                            value.to_string().as_str(),
-                        )
-                    }
-                    new_args.push(new_value);
+                    continue;
+                }
+                if text_output {
+                    println!("=> Executing: {}", cmd_name.cyan());
+                }
+                executed.insert(stage, cmd_name, hook);
+                reports.push(self.run_hook(stage, cmd_name, hook, &env, scope)?);
+            }
+            if text_output {
+                println!("\n \u{2728} Done executing {} scripts!", stage.name());
+            }
+        }
+
+        Ok(reports)
+    }
+}
+
+impl<'rtctx> ProfileContext<'rtctx> {
+    /// Execute the hook (re-executing it if retries are set).
+    /// A failed hook is an error unless its failure is allowed
+    fn run_hook(
+        &self,
+        stage: HookStage,
+        cmd_name: &str,
+        hook: &Hook,
+        env: &[(String, String)],
+        scope: &HookScope,
+    ) -> CmdResult<HookReport> {
+        let text_output = self.rt_context.is_text_output();
+        let raw_args = match hook.command() {
+            Ok(args) => args,
+            Err(reason) => {
+                return Err(Error::InvalidHookDefinition {
+                    profile: self.profile_name.clone(),
+                    hook: cmd_name.to_string(),
+                    stage,
+                    reason,
+                })
+            }
+        };
+        let local_args = self.process_args(&raw_args, scope)?;
+        let detailed = hook.detailed();
+        let timeout = detailed
+            .and_then(|hook| hook.timeout_secs)
+            .map(std::time::Duration::from_secs);
+        let attempts = 1 + detailed.and_then(|hook| hook.retries).unwrap_or(0);
+
+        let mut command = std::process::Command::new(&local_args[0]);
+        command
+            .args(&local_args[1..])
+            .envs(env.iter().cloned())
+            .current_dir(
+                std::path::Path::new(&self.rt_context.base_path)
+                    .join(detailed.and_then(|hook| hook.cwd.as_deref()).unwrap_or(".")),
+            );
+        if let Some(hook_env) = detailed.and_then(|hook| hook.env.as_ref()) {
+            command.envs(hook_env.iter());
+        }
+
+        let log_path = self.hook_log_path(stage, cmd_name);
+        let mut sink = process::OutputSink {
+            prefix: text_output.then(|| format!("[{}]", cmd_name)),
+            log: None,
+        };
+        if let Some(log_path) = &log_path {
+            let created = std::fs::create_dir_all(log_path.parent().unwrap())
+                .and_then(|_| std::fs::File::create(log_path));
+            match created {
+                Ok(file) => sink.log = Some(std::sync::Arc::new(std::sync::Mutex::new(file))),
+                Err(err) => {
+                    return Err(Error::CannotCreateHookLog {
+                        profile: self.profile_name.clone(),
+                        hook: cmd_name.to_string(),
+                        path: log_path.clone(),
+                        source: err,
+                    })
+                }
+            }
+        }
+
+        let mut attempt = 1;
+        let output = loop {
+            let output = match process::run(&mut command, timeout, &sink) {
+                Ok(output) => output,
+                Err(err) => {
+                    return Err(Error::CannotExecuteSubprocess {
+                        profile: self.profile_name.clone(),
+                        hook: cmd_name.to_string(),
+                        stage,
+                        source: err,
+                    })
+                }
+            };
+            if output.success() || attempt >= attempts {
+                break output;
+            }
+            attempt += 1;
+            if text_output {
+                println!(
+                    "=> Retrying: {} (attempt {}/{})",
+                    cmd_name.cyan(),
+                    attempt,
+                    attempts
+                );
+            }
+        };
+
+        let stdout_output = output.stdout.clone();
+        let stderr_output = output.stderr.clone();
+        let exit_code = output.status.and_then(|status| status.code());
+        if !output.success() {
+            let allow_failure = detailed
+                .and_then(|hook| hook.allow_failure)
+                .unwrap_or_default();
+            if text_output {
+                if allow_failure {
+                    eprintln!(
+                        "{}",
+                        " \u{1F4A5} Oops! There is an error, but it's allowed".white()
+                    );
                 } else {
-                    new_args.push(arg.clone());
+                    eprintln!("{}", " \u{1F4A5} Oops! There is an error".white());
                 }
+            }
+            if !allow_failure {
+                return if output.timed_out() {
+                    Err(Error::HookTimedOut {
+                        profile: self.profile_name.clone(),
+                        hook: cmd_name.to_string(),
+                        stage,
+                        attempts: attempt,
+                        timeout_secs: timeout.map_or(0, |timeout| timeout.as_secs()),
+                        stderr: stderr_output,
+                    })
+                } else {
+                    Err(Error::SubproccessCallFailed {
+                        profile: self.profile_name.clone(),
+                        hook: cmd_name.to_string(),
+                        stage,
+                        attempts: attempt,
+                        exit_code,
+                        stderr: stderr_output,
+                    })
+                };
+            }
+        }
+
+        Ok(HookReport {
+            name: cmd_name.to_string(),
+            stage,
+            command: local_args,
+            exit_code,
+            success: output.success(),
+            timed_out: output.timed_out(),
+            attempts: attempt,
+            stdout: stdout_output,
+            stderr: stderr_output,
+            log: log_path.map(|path| path.display().to_string()),
+        })
+    }
+
+    /// `.weee/logs/<timestamp>-<profile>-<stage>-<hook>.log` if saving logs is enabled
+    fn hook_log_path(&self, stage: HookStage, cmd_name: &str) -> Option<std::path::PathBuf> {
+        let save_logs = self
+            .profile_model
+            .hooks
+            .as_ref()
+            .and_then(|hooks| hooks.save_logs)
+            .unwrap_or_default();
+        if !save_logs {
+            return None;
+        }
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let file_name: String = format!(
+            "{}-{}-{}-{}.log",
+            timestamp,
+            self.profile_name,
+            stage.name(),
+            cmd_name
+        )
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "-_.".contains(c) {
+                c
             } else {
-                new_args.push(arg.clone());
+                '_'
             }
+        })
+        .collect();
+        Some(self.rt_context.logs_path().join(file_name))
+    }
+}
+
+/// Hooks executed in a transaction. A hook with the same name
+/// and definition (i.e. from a shared include) is executed once
+#[derive(Default)]
+pub struct ExecutedHooks {
+    hooks: Vec<(HookStage, String, Hook)>,
+}
+
+impl ExecutedHooks {
+    fn contains(&self, stage: HookStage, name: &str, hook: &Hook) -> bool {
+        self.hooks
+            .iter()
+            .any(|executed| executed.0 == stage && executed.1 == name && executed.2 == *hook)
+    }
+
+    fn insert(&mut self, stage: HookStage, name: &str, hook: &Hook) {
+        self.hooks.push((stage, name.to_string(), hook.clone()));
+    }
+}
+
+/// What hooks are executed for
+pub struct HookScope<'a> {
+    pub changed_version: &'a ChangedVersion,
+    pub bumped_part: Option<&'a str>,
+    pub read_only: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum HookStage {
+    Before,
+    Afterwords,
+    OnFailure,
+}
+
+impl HookStage {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookStage::Before => "before",
+            HookStage::Afterwords => "afterwords",
+            HookStage::OnFailure => "on-failure",
         }
-        Ok(new_args)
     }
+}
 
-    pub fn execute_afterword_hooks(&self, changed_version: &ChangedVersion) -> CmdResult {
-        if let Some(hooks) = &self.profile_model.hooks {
-            if let Some(afterwords) = &hooks.afterwords {
-                println!("\n \u{1F50D} Founded some afterwords hooks...");
-                for (cmd_name, args) in afterwords {
-                    println!("=> Executing: {}", cmd_name.cyan());
-                    let local_args = self.process_args(&args, &changed_version)?;
-
-                    let executed_command = std::process::Command::new(local_args[0].clone())
-                        .args(&local_args[1..])
-                        .stdout(std::process::Stdio::piped())
-                        .stderr(std::process::Stdio::piped())
-                        .output();
-
-                    match executed_command {
-                        Err(err) => {
-                            return show_err!(
-                                [CannotExecuteSubprocess]
-                                => "An error occured while executing subproccess",
-                                error=err,
-                                step=cmd_name
-                            )
-                        }
-                        Ok(cmd) => {
-                            let stdout_output = String::from_utf8_lossy(&cmd.stdout);
-                            print!("{}", &stdout_output.bright_black());
-                            if !cmd.status.success() {
-                                eprintln!("{}", " \u{1F4A5} Oops! There is an error".white());
-                                let stderr_output = String::from_utf8_lossy(&cmd.stderr);
-                                return show_err!(
-                                    [SubproccessCallFailed]
-                                    => "An error occured while executing subproccess",
-                                    stderr=stderr_output,
-                                    command=cmd_name
-                                );
-                            }
-                        }
-                    };
+// Applying a changed version to the project
+impl<'rtctx> ProfileContext<'rtctx> {
+    /// Run the whole bump pipeline for an already computed version:
+    /// before hooks, files replacements, profile storage update and afterword hooks.
+    /// If replacements or afterword hooks fail, on-failure hooks are executed
+    /// and the original error is returned
+    pub fn apply_changed_version(
+        &mut self,
+        changed_version: &ChangedVersion,
+        bumped_part: Option<&str>,
+        read_only: bool,
+        report: &mut Report,
+    ) -> CmdResult {
+        apply_changed_versions(
+            &mut [ProfileChange {
+                profile_ctx: self,
+                changed_version,
+                bumped_part,
+                dependencies: vec![],
+                references_only: false,
+                report,
+            }],
+            read_only,
+        )
+    }
+}
+
+/// A profile's share of a transaction
+pub struct ProfileChange<'p, 'rtctx> {
+    pub profile_ctx: &'p mut ProfileContext<'rtctx>,
+    pub changed_version: &'p ChangedVersion,
+    pub bumped_part: Option<&'p str>,
+    /// Bumped dependencies whose references are rewritten, by name
+    pub dependencies: Vec<(&'p str, &'p ChangedVersion)>,
+    /// Only references to dependencies are rewritten. The profile's version,
+    /// changelog and history are left as they are, no hook is executed and no tag is created
+    pub references_only: bool,
+    pub report: &'p mut Report,
+}
+
+impl<'p, 'rtctx> ProfileChange<'p, 'rtctx> {
+    fn scope(&self, read_only: bool) -> HookScope<'p> {
+        HookScope {
+            changed_version: self.changed_version,
+            bumped_part: self.bumped_part,
+            read_only,
+        }
+    }
+}
+
+/// Apply changed versions of several profiles as one transaction.
+/// Every profile is prepared before anything is changed, a file shared by profiles
+/// is written once with all the replacements, the git integration makes one commit
+/// with every tag and a hook shared by profiles is executed once.
+/// Stages go profile by profile: before hooks, files, storages, git, afterword hooks.
+/// Files and storages are written together and restored if git fails, so only
+/// afterword hooks run after the project is changed for good.
+/// If anything after before hooks fails, on-failure hooks of every profile are executed
+pub fn apply_changed_versions(changes: &mut [ProfileChange], read_only: bool) -> CmdResult {
+    let mut prepared = vec![];
+    for change in changes.iter() {
+        let scope = change.scope(read_only);
+        let mut changed_files = if change.references_only {
+            vec![]
+        } else {
+            change
+                .profile_ctx
+                .prepare_replacemts(change.changed_version)?
+        };
+        for (dependency, changed_version) in change.dependencies.iter() {
+            changed_files.extend(
+                change
+                    .profile_ctx
+                    .prepare_dependency_replacements(dependency, changed_version)?,
+            );
+        }
+        let git_plan =
+            change
+                .profile_ctx
+                .prepare_git(&changed_files, &scope, change.references_only)?;
+        prepared.push((changed_files, git_plan));
+    }
+
+    // One commit is made, so every profile must be in the same repository
+    let repositories: Vec<(String, std::path::PathBuf)> = changes
+        .iter()
+        .zip(&prepared)
+        .filter_map(|(change, (_changed_files, git_plan))| {
+            let toplevel = git_plan.as_ref()?.repository.toplevel();
+            Some((change.profile_ctx.qualified_name(), toplevel.to_path_buf()))
+        })
+        .collect();
+    if repositories
+        .iter()
+        .any(|(_profile, toplevel)| *toplevel != repositories[0].1)
+    {
+        return Err(Error::DifferentGitRepositories {
+            profiles: repositories,
+        });
+    }
+
+    // All the tags are created on one commit, git cannot have two of the same name
+    let mut tagged: Vec<(&str, String)> = vec![];
+    for (change, (_changed_files, git_plan)) in changes.iter().zip(&prepared) {
+        if let Some(tag) = git_plan
+            .as_ref()
+            .and_then(|git_plan| git_plan.tag.as_deref())
+        {
+            let profile = change.profile_ctx.qualified_name();
+            if let Some((_tag, other)) = tagged.iter().find(|(other_tag, _other)| *other_tag == tag)
+            {
+                return Err(Error::GitTagIsNotUnique {
+                    tag: tag.to_string(),
+                    profiles: vec![other.clone(), profile],
+                });
+            }
+            tagged.push((tag, profile));
+        }
+    }
+
+    let mut executed = ExecutedHooks::default();
+    for change in changes.iter_mut().filter(|change| !change.references_only) {
+        let scope = change.scope(read_only);
+        let hook_reports =
+            change
+                .profile_ctx
+                .execute_stage_hooks(HookStage::Before, &scope, &mut executed)?;
+        change.report.hooks.extend(hook_reports);
+    }
+
+    if let Err(err) = apply_prepared(changes, &prepared, read_only, &mut executed) {
+        for change in changes.iter_mut().filter(|change| !change.references_only) {
+            let scope = change.scope(read_only);
+            match change.profile_ctx.execute_stage_hooks(
+                HookStage::OnFailure,
+                &scope,
+                &mut executed,
+            ) {
+                Ok(failure_reports) => change.report.hooks.extend(failure_reports),
+                Err(failure_err) => {
+                    return Err(Error::OnFailureHooksFailed {
+                        error: Box::new(err),
+                        on_failure_error: Box::new(failure_err),
+                    })
+                }
+            }
+        }
+        return Err(err);
+    }
+
+    if !read_only {
+        // Dependents are recorded too, so their references are reverted by `undo`
+        for (index, change) in changes.iter().enumerate() {
+            let action = match change.bumped_part {
+                Some(_part) => history::Action::Bump,
+                None if change.references_only => history::Action::Bump,
+                None => history::Action::Move,
+            };
+            let dependencies = change
+                .dependencies
+                .iter()
+                .map(|(dependency, changed_version)| HistoryDependency {
+                    profile: dependency.to_string(),
+                    old: changed_version.old.clone(),
+                    new: changed_version.new.clone(),
+                })
+                .collect();
+            change.profile_ctx.record_history(
+                action,
+                change.report,
+                index > 0,
+                change.references_only,
+                dependencies,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn apply_prepared(
+    changes: &mut [ProfileChange],
+    prepared: &[(Vec<ChangedFile>, Option<GitPlan>)],
+    read_only: bool,
+    executed: &mut ExecutedHooks,
+) -> CmdResult {
+    let mut pending = PendingFiles::default();
+    let mut changelog_updates = vec![];
+    for (change, (changed_files, _git_plan)) in changes.iter_mut().zip(prepared) {
+        let scope = change.scope(read_only);
+        let changelog_update = if change.references_only {
+            None
+        } else {
+            change.profile_ctx.prepare_changelog(&scope, &pending)?
+        };
+        if let Some(changelog_update) = &changelog_update {
+            pending.set(
+                changelog_update.path.clone(),
+                changelog_update.content.clone(),
+            );
+        }
+        change.report.files = change
+            .profile_ctx
+            .replace_in_files(changed_files, &mut pending)?;
+        if !change.references_only {
+            change.profile_ctx.set_storage(change.changed_version);
+            pending.set(
+                change.profile_ctx.profile_path(),
+                change.profile_ctx.profile_doc.to_string(),
+            );
+        }
+        changelog_updates.push(changelog_update);
+    }
+    if !read_only {
+        pending.write()?;
+    }
+
+    for (change, changelog_update) in changes.iter_mut().zip(changelog_updates) {
+        if let Some(changelog_update) = changelog_update {
+            change.profile_ctx.print_changelog_update(&changelog_update);
+            change.report.changelog = Some(changelog_update.report);
+        }
+    }
+
+    let git_plans: Vec<&GitPlan> = prepared
+        .iter()
+        .filter_map(|(_changed_files, git_plan)| git_plan.as_ref())
+        .collect();
+    if let Some(first_change) = changes.first().filter(|_change| !git_plans.is_empty()) {
+        let commit =
+            match commit_and_tag(first_change.profile_ctx.rt_context, &git_plans, read_only) {
+                Ok(commit) => commit,
+                Err(err) => {
+                    pending.restore();
+                    return Err(err);
+                }
+            };
+        for (change, (_changed_files, git_plan)) in changes.iter_mut().zip(prepared) {
+            if let Some(git_plan) = git_plan {
+                change.report.git = Some(GitReport {
+                    commit: commit.clone(),
+                    tag: git_plan.tag.clone().filter(|_tag| !read_only),
+                });
+            }
+        }
+    }
+
+    for change in changes.iter_mut().filter(|change| !change.references_only) {
+        let scope = change.scope(read_only);
+        let hook_reports =
+            change
+                .profile_ctx
+                .execute_stage_hooks(HookStage::Afterwords, &scope, executed)?;
+        change.report.hooks.extend(hook_reports);
+    }
+    Ok(())
+}
+
+/// What the git integration is going to do after files are changed
+pub struct GitPlan {
+    repository: git::Repository,
+    /// Files changed by the bump including the profile itself
+    files: Vec<std::path::PathBuf>,
+    /// Files are committed (with messages of other profiles if it has none)
+    commit: bool,
+    commit_message: Option<String>,
+    tag: Option<String>,
+    tag_message: Option<String>,
+}
+
+// Git integration
+impl<'rtctx> ProfileContext<'rtctx> {
+    /// Render git templates and check the repository before anything is changed.
+    /// If only references to dependencies are rewritten, nothing is rendered
+    /// and just the changed files are committed
+    fn prepare_git(
+        &self,
+        prepared_changed_files: &[ChangedFile],
+        scope: &HookScope,
+        references_only: bool,
+    ) -> CmdResult<Option<GitPlan>> {
+        let git_config = match &self.profile_model.git {
+            Some(git_config) => git_config,
+            None => return Ok(None),
+        };
+        let repository = git::Repository::open(std::path::Path::new(&self.rt_context.base_path))?;
+
+        let mut files = vec![];
+        if !references_only {
+            files.push(self.profile_path());
+            files.extend(self.changelog_path());
+        }
+        for file in prepared_changed_files {
+            let path = self.rt_context.project_file_path(&file.name);
+            if !files.contains(&path) {
+                files.push(path);
+            }
+        }
+
+        if git_config.require_clean.unwrap_or_default() {
+            // The history and hook logs are local state, they are never committed
+            let mut allowed = files.clone();
+            allowed.push(self.rt_context.history_path());
+            allowed.push(self.rt_context.logs_path());
+            repository.ensure_clean(&allowed)?;
+        }
+        let commit = git_config.commit.unwrap_or_default();
+        if references_only {
+            return Ok(Some(GitPlan {
+                repository,
+                files,
+                commit,
+                commit_message: None,
+                tag: None,
+                tag_message: None,
+            }));
+        }
+
+        let globals = self.hook_globals(scope)?;
+        let render = |template: &Option<String>, source: &str| match template {
+            Some(template) => self.render_liquid(template, &globals, source).map(Some),
+            None => Ok(None),
+        };
+        let commit_message = if commit {
+            Some(
+                self.render_liquid(
+                    git_config
+                        .commit_message
+                        .as_deref()
+                        .unwrap_or(DEFAULT_COMMIT_MESSAGE),
+                    &globals,
+                    "<git.commit_message>",
+                )?,
+            )
+        } else {
+            None
+        };
+        let tag = render(&git_config.tag, "<git.tag>")?;
+        let tag_message = render(&git_config.tag_message, "<git.tag_message>")?;
+
+        if let Some(tag) = &tag {
+            if repository.tag_exists(tag)? {
+                return Err(Error::GitTagAlreadyExists {
+                    profile: self.profile_name.clone(),
+                    tag: tag.clone(),
+                });
+            }
+        }
+
+        Ok(Some(GitPlan {
+            repository,
+            files,
+            commit,
+            commit_message,
+            tag,
+            tag_message,
+        }))
+    }
+
+    /// The highest tag matching a glob pattern parsed with the main view.
+    /// The pattern's literal prefix (`v` in `v*`) is stripped before parsing,
+    /// tags that do not match the view are skipped
+    pub fn latest_git_tag(&self, pattern: &str) -> CmdResult<(String, Version)> {
+        let repository = git::Repository::open(std::path::Path::new(&self.rt_context.base_path))?;
+        let prefix = &pattern[..pattern.find(['*', '?', '[']).unwrap_or(pattern.len())];
+
+        let mut latest: Option<(String, Version)> = None;
+        for tag in repository.tags(pattern)? {
+            let version = match self.parse_main_view(tag.strip_prefix(prefix).unwrap_or(&tag))? {
+                Some(version) => version,
+                None => continue,
+            };
+            let is_higher = match &latest {
+                Some((_tag, latest_version)) => {
+                    self.compare_versions(&version, latest_version).is_gt()
+                }
+                None => true,
+            };
+            if is_higher {
+                latest = Some((tag, version));
+            }
+        }
+
+        match latest {
+            Some(latest) => Ok(latest),
+            None => Err(Error::NoMatchingGitTag {
+                profile: self.profile_name.clone(),
+                pattern: pattern.to_string(),
+            }),
+        }
+    }
+
+    /// `conventional_commits.tag_pattern` if it's set. Otherwise it's the `git.tag`
+    /// template with liquid replaced by `*` (`v{{ new_version }}` gives `v*`),
+    /// so tags which are not versions (i.e. `docs-published`) are skipped.
+    /// Without `git.tag` it's `v*`
+    pub fn version_tag_pattern(&self) -> String {
+        if let Some(pattern) = self
+            .profile_model
+            .conventional_commits
+            .as_ref()
+            .and_then(|config| config.tag_pattern.as_ref())
+        {
+            return pattern.clone();
+        }
+        let template = match self
+            .profile_model
+            .git
+            .as_ref()
+            .and_then(|git_config| git_config.tag.as_deref())
+        {
+            Some(template) => template,
+            None => return "v*".to_string(),
+        };
+
+        let mut pattern = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{").into_iter().chain(rest.find("{%")).min() {
+            pattern.push_str(&rest[..start]);
+            if !pattern.ends_with('*') {
+                pattern.push('*');
+            }
+            let closing = if rest[start..].starts_with("{{") {
+                "}}"
+            } else {
+                "%}"
+            };
+            rest = match rest[start..].find(closing) {
+                Some(end) => &rest[start + end + closing.len()..],
+                None => "",
+            };
+        }
+        pattern.push_str(rest);
+        pattern
+    }
+
+    /// The last version tag (see `conventional_commits.tag_pattern`)
+    /// and commits made since it, newest first
+    fn commits_since_last_tag(&self) -> CmdResult<(Option<String>, Vec<git::Commit>)> {
+        let tag_pattern = self.version_tag_pattern();
+        let repository = git::Repository::open(std::path::Path::new(&self.rt_context.base_path))?;
+        let since_tag = repository.last_tag(&tag_pattern)?;
+        let commits = repository.commits_since(since_tag.as_deref())?;
+        Ok((since_tag, commits))
+    }
+
+    /// Pick a part to bump from Conventional Commits made since the last
+    /// version tag. The most significant part (the first in the profile) wins
+    pub fn auto_bump_part(&self) -> CmdResult<AutoReport> {
+        let default_config = ConventionalCommits::default();
+        let config = self
+            .profile_model
+            .conventional_commits
+            .as_ref()
+            .unwrap_or(&default_config);
+        let (since_tag, new_commits) = self.commits_since_last_tag()?;
+
+        let mut commits = vec![];
+        let mut chosen: Option<(usize, String)> = None;
+        for commit in new_commits {
+            let part = commit.conventional().and_then(|header| {
+                if header.breaking {
+                    Some(config.breaking_part())
+                } else {
+                    config.part_of_type(&header.commit_type)
+                }
+            });
+            if let Some(part) = &part {
+                self.check_part_exists(part)?;
+                let position = self
+                    .profile_model
+                    .parts
+                    .keys()
+                    .position(|part_name| part_name == part)
+                    .unwrap_or_default();
+                if chosen
+                    .as_ref()
+                    .is_none_or(|(chosen, _part)| position < *chosen)
+                {
+                    chosen = Some((position, part.clone()));
+                }
+            }
+            commits.push(CommitReport {
+                hash: commit.hash.clone(),
+                subject: commit.subject().to_string(),
+                part,
+            });
+        }
+
+        match chosen {
+            Some((_position, part)) => Ok(AutoReport {
+                since_tag,
+                commits,
+                part,
+            }),
+            None => Err(Error::NoCommitsToRelease {
+                profile: self.profile_name.clone(),
+                since_tag,
+                commits: commits.len(),
+            }),
+        }
+    }
+}
+
+/// Make one commit with files of every plan which commits and create all the tags on it.
+/// Plans without a message (references to dependencies) only add their files.
+/// Different commit messages of several profiles are joined with blank lines.
+/// Returns the commit's hash
+fn commit_and_tag(
+    rt_context: &RTContext,
+    git_plans: &[&GitPlan],
+    read_only: bool,
+) -> CmdResult<Option<String>> {
+    let text_output = rt_context.is_text_output();
+    let mut messages: Vec<&str> = vec![];
+    let mut files: Vec<std::path::PathBuf> = vec![];
+    for git_plan in git_plans.iter().filter(|git_plan| git_plan.commit) {
+        if let Some(message) = &git_plan.commit_message {
+            if !messages.contains(&message.as_str()) {
+                messages.push(message);
+            }
+        }
+        for file in git_plan.files.iter() {
+            if !files.contains(file) {
+                files.push(file.clone());
+            }
+        }
+    }
+    let message = if messages.is_empty() {
+        None
+    } else {
+        Some(messages.join("\n\n"))
+    };
+
+    if read_only {
+        if text_output {
+            if let Some(message) = &message {
+                println!(" \u{1F4E6} Would commit (read-only): {}", message.cyan());
+            }
+            for tag in git_plans
+                .iter()
+                .filter_map(|git_plan| git_plan.tag.as_ref())
+            {
+                println!(" \u{1F516} Would tag (read-only): {}", tag.cyan());
+            }
+        }
+        return Ok(None);
+    }
+
+    let mut commit = None;
+    if let Some(message) = &message {
+        let hash = git_plans[0].repository.commit(&files, message)?;
+        if text_output {
+            println!(" \u{1F4E6} Committed {}: {}", hash.yellow(), message.cyan());
+        }
+        commit = Some(hash);
+    }
+    let mut tagged: Vec<&GitPlan> = vec![];
+    for git_plan in git_plans.iter() {
+        if let Some(tag) = &git_plan.tag {
+            if let Err(err) = git_plan
+                .repository
+                .tag(tag, git_plan.tag_message.as_deref())
+            {
+                // Best effort: the original error matters more than failures of the rollback
+                for tagged_plan in tagged.iter() {
+                    let _deleted = tagged_plan
+                        .repository
+                        .delete_tag(tagged_plan.tag.as_deref().unwrap_or_default());
                 }
-                println!("\n \u{2728} Done executing afterwords scripts!");
+                if commit.is_some() {
+                    let _reset = git_plans[0].repository.reset_last_commit(&files);
+                }
+                return Err(err);
+            }
+            tagged.push(git_plan);
+            if text_output {
+                println!(" \u{1F516} Tagged: {}", tag.cyan());
+            }
+        }
+    }
+    Ok(commit)
+}
+
+/// New content of the changelog computed before any file is written
+pub struct ChangelogUpdate {
+    path: std::path::PathBuf,
+    content: String,
+    report: ChangelogReport,
+}
+
+// Changelog
+impl<'rtctx> ProfileContext<'rtctx> {
+    fn changelog_path(&self) -> Option<std::path::PathBuf> {
+        let changelog = self.profile_model.changelog.as_ref()?;
+        Some(
+            self.rt_context
+                .project_file_path(changelog.path.as_deref().unwrap_or(DEFAULT_CHANGELOG_PATH)),
+        )
+    }
+
+    /// Rename the "Unreleased" section to the new version
+    /// and put a fresh "Unreleased" section above it
+    fn prepare_changelog(
+        &self,
+        scope: &HookScope,
+        pending: &PendingFiles,
+    ) -> CmdResult<Option<ChangelogUpdate>> {
+        let changelog = match &self.profile_model.changelog {
+            Some(changelog) => changelog,
+            None => return Ok(None),
+        };
+        let name = changelog.path.as_deref().unwrap_or(DEFAULT_CHANGELOG_PATH);
+        let path = self.rt_context.project_file_path(name);
+        let unreleased = changelog
+            .unreleased
+            .as_deref()
+            .unwrap_or(DEFAULT_UNRELEASED_HEADING);
+
+        let content = pending.content(&path)?;
+        let heading_start = match content
+            .match_indices(unreleased)
+            .map(|(index, _)| index)
+            .find(|index| {
+                let line_start = *index == 0 || content[..*index].ends_with('\n');
+                let line_end = content[*index + unreleased.len()..]
+                    .chars()
+                    .next()
+                    .is_none_or(|c| c == '\n' || c == '\r');
+                line_start && line_end
+            }) {
+            Some(index) => index,
+            None => {
+                return Err(Error::ChangelogHasNoUnreleasedSection {
+                    profile: self.profile_name.clone(),
+                    file: name.to_string(),
+                    heading: unreleased.to_string(),
+                })
+            }
+        };
+
+        let heading = self.render_liquid(
+            changelog
+                .heading
+                .as_deref()
+                .unwrap_or(DEFAULT_CHANGELOG_HEADING),
+            &self.hook_globals(scope)?,
+            "<changelog.heading>",
+        )?;
+        let (entries, entries_count) = if changelog.from_commits.unwrap_or_default() {
+            self.changelog_entries(changelog)?
+        } else {
+            (String::new(), 0)
+        };
+
+        let heading_end = heading_start + unreleased.len();
+        let inserted = format!("\n\n{}{}", heading, entries);
+        let new_content = format!(
+            "{}{}{}{}",
+            &content[..heading_start],
+            unreleased,
+            inserted,
+            &content[heading_end..]
+        );
+        Ok(Some(ChangelogUpdate {
+            path,
+            content: new_content,
+            report: ChangelogReport {
+                path: name.to_string(),
+                heading,
+                entries: entries_count,
+                unreleased: unreleased.to_string(),
+                inserted,
+            },
+        }))
+    }
+
+    /// Markdown subsections with commits grouped by the changelog's sections.
+    /// Commits of unknown types are skipped
+    fn changelog_entries(&self, changelog: &Changelog) -> CmdResult<(String, usize)> {
+        let (_since_tag, commits) = self.commits_since_last_tag()?;
+        let mut sections: LinkedHashMap<String, Vec<String>> = LinkedHashMap::new();
+        if let Some(configured) = &changelog.sections {
+            for title in configured.values() {
+                sections.entry(title.clone()).or_insert_with(Vec::new);
+            }
+        }
+
+        let mut count = 0;
+        // Oldest commits go first
+        for commit in commits.iter().rev() {
+            let header = match commit.conventional() {
+                Some(header) => header,
+                None => continue,
+            };
+            let title = match changelog.section_of_type(&header.commit_type) {
+                Some(title) => title,
+                None => continue,
+            };
+            let mut entry = String::from("- ");
+            if header.breaking {
+                entry.push_str("**BREAKING** ");
+            }
+            if let Some(scope) = &header.scope {
+                entry.push_str(&format!("**{}:** ", scope));
+            }
+            entry.push_str(&header.description);
+            sections.entry(title).or_insert_with(Vec::new).push(entry);
+            count += 1;
+        }
+
+        let mut rendered = String::new();
+        for (title, entries) in sections.iter() {
+            if entries.is_empty() {
+                continue;
             }
+            rendered.push_str(&format!("\n\n### {}\n\n{}", title, entries.join("\n")));
+        }
+        Ok((rendered, count))
+    }
+
+    fn print_changelog_update(&self, update: &ChangelogUpdate) {
+        if self.rt_context.is_text_output() {
+            println!(
+                "[{}]: {} => {}",
+                update
+                    .path
+                    .to_str()
+                    .unwrap_or("<cannot render path>")
+                    .magenta(),
+                update.report.unreleased.red(),
+                update.report.heading.green(),
+            );
         }
+    }
+}
 
+// History
+impl<'rtctx> ProfileContext<'rtctx> {
+    /// Append a successful change with hashes of the touched files to the history
+    fn record_history(
+        &self,
+        action: history::Action,
+        report: &Report,
+        with_previous: bool,
+        references_only: bool,
+        dependencies: Vec<HistoryDependency>,
+    ) -> CmdResult {
+        let mut files: Vec<HistoryFile> = vec![];
+        let touched = report
+            .files
+            .iter()
+            .map(|file| &file.path)
+            .chain(report.changelog.iter().map(|changelog| &changelog.path));
+        for path in touched {
+            if files.iter().any(|file| file.path == *path) {
+                continue;
+            }
+            files.push(HistoryFile {
+                path: path.clone(),
+                sha256: history::hash_file(&self.rt_context.project_file_path(path))?,
+            });
+        }
+
+        let entry = HistoryEntry {
+            timestamp: history::timestamp(),
+            action,
+            profile: self.qualified_name(),
+            with_previous,
+            references_only,
+            dependencies,
+            bumped_part: report.bumped_part.clone(),
+            old: report.old.clone(),
+            new: report.new.clone(),
+            files,
+            changelog: report.changelog.as_ref().map(|changelog| HistoryChangelog {
+                path: changelog.path.clone(),
+                unreleased: changelog.unreleased.clone(),
+                inserted: changelog.inserted.clone(),
+            }),
+            hooks: report
+                .hooks
+                .iter()
+                .map(|hook| HistoryHook {
+                    name: hook.name.clone(),
+                    stage: hook.stage,
+                    success: hook.success,
+                })
+                .collect(),
+        };
+        history::append(&self.rt_context.history_path(), &entry)
+    }
+
+    /// Refuse to undo if the version or any touched file has changed since the entry
+    fn check_undoable(&self, entry: &HistoryEntry) -> CmdResult {
+        if self.current_version() != entry.new.parts {
+            return Err(Error::VersionChangedSinceBump {
+                profile: self.profile_name.clone(),
+                expected: entry.new.version.clone(),
+                current: self.version_to_string(&self.current_version()),
+            });
+        }
+        for file in entry.files.iter() {
+            let hash = history::hash_file(&self.rt_context.project_file_path(&file.path))?;
+            if hash != file.sha256 {
+                return Err(Error::FileChangedSinceBump {
+                    profile: self.profile_name.clone(),
+                    file: file.path.clone(),
+                });
+            }
+        }
         Ok(())
     }
+
+    /// Replace the entry's new version with the old one in the pending files,
+    /// references to its dependencies are changed back too
+    fn revert(
+        &mut self,
+        entry: &HistoryEntry,
+        pending: &mut PendingFiles,
+        report: &mut Report,
+    ) -> CmdResult {
+        let changed_version = ChangedVersion {
+            old: entry.new.parts.clone(),
+            new: entry.old.parts.clone(),
+        };
+        let mut prepared_changed_files = if entry.references_only {
+            vec![]
+        } else {
+            self.prepare_replacemts(&changed_version)?
+        };
+        for dependency in entry.dependencies.iter() {
+            let changed_dependency = ChangedVersion {
+                old: dependency.new.clone(),
+                new: dependency.old.clone(),
+            };
+            prepared_changed_files.extend(
+                self.prepare_dependency_replacements(&dependency.profile, &changed_dependency)?,
+            );
+        }
+        if let Some(changelog) = &entry.changelog {
+            let changelog_update = self.revert_changelog(changelog, pending)?;
+            self.print_changelog_update(&changelog_update);
+            pending.set(changelog_update.path, changelog_update.content);
+        }
+        report.files = self.replace_in_files(&prepared_changed_files, pending)?;
+        if !entry.references_only {
+            self.set_storage(&changed_version);
+            pending.set(self.profile_path(), self.profile_doc.to_string());
+        }
+        Ok(())
+    }
+
+    fn revert_changelog(
+        &self,
+        changelog: &HistoryChangelog,
+        pending: &PendingFiles,
+    ) -> CmdResult<ChangelogUpdate> {
+        let path = self.rt_context.project_file_path(&changelog.path);
+        let promoted = format!("{}{}", changelog.unreleased, changelog.inserted);
+        let content = match pending.content(&path) {
+            Ok(content) if content.contains(&promoted) => content,
+            _ => {
+                return Err(Error::FileChangedSinceBump {
+                    profile: self.profile_name.clone(),
+                    file: changelog.path.clone(),
+                })
+            }
+        };
+        Ok(ChangelogUpdate {
+            path,
+            content: content.replacen(&promoted, &changelog.unreleased, 1),
+            // Reverted the other way round: the version's heading becomes "Unreleased"
+            report: ChangelogReport {
+                path: changelog.path.clone(),
+                heading: changelog.unreleased.clone(),
+                entries: 0,
+                unreleased: changelog
+                    .inserted
+                    .trim_start()
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                inserted: String::new(),
+            },
+        })
+    }
+}
+
+/// Revert entries of one transaction (see `history::last_undoable`) by replacing
+/// new versions with old ones, the last changed profile goes first.
+/// Hooks and git integration are not involved. Nothing is written if the version
+/// or any touched file of any profile has changed since then
+pub fn undo_transaction(
+    undone: &mut [(ProfileContext, &HistoryEntry, Report)],
+    read_only: bool,
+) -> CmdResult {
+    for (profile_ctx, entry, _report) in undone.iter() {
+        profile_ctx.check_undoable(entry)?;
+    }
+
+    let mut pending = PendingFiles::default();
+    for (profile_ctx, entry, report) in undone.iter_mut().rev() {
+        profile_ctx.revert(entry, &mut pending, report)?;
+    }
+    if read_only {
+        return Ok(());
+    }
+    pending.write()?;
+
+    for (index, (profile_ctx, entry, report)) in undone.iter().enumerate() {
+        profile_ctx.record_history(
+            history::Action::Undo,
+            report,
+            index > 0,
+            entry.references_only,
+            vec![],
+        )?;
+    }
+    Ok(())
 }
@@ -0,0 +1,169 @@
+//! Profiles referring to versions of other profiles:
+//!
+//! ```toml
+//! # .weee/app.version.toml
+//! [dependencies.mylib]
+//! bump = "patch"
+//!
+//! [[dependencies.mylib.files."Cargo.toml"]]
+//! version.view = "^{major}.{minor}"
+//! version.placement = "mylib = \"{version}\""
+//! ```
+//!
+//! When `mylib` is bumped, its references in `app` are rewritten and `app` gets
+//! its own `patch` bump, which goes on to profiles depending on `app`.
+//! Without `bump` only the references are rewritten.
+//! Dependencies are named the way the root lists profiles, i.e. `packages/api:mylib`.
+
+use std::collections::HashMap;
+
+use colored::Colorize;
+use linked_hash_map::LinkedHashMap;
+use toml_edit::Item;
+
+use crate::composition;
+use crate::context::RTContext;
+use crate::error::Error;
+use crate::handleable::CmdResult;
+
+pub struct Graph {
+    /// Dependencies of every profile in the order they are declared
+    dependencies: LinkedHashMap<String, Vec<String>>,
+    profiles: Vec<String>,
+}
+
+impl Graph {
+    /// Names of dependencies of every profile of the context and its members.
+    /// Nothing else is checked, so profiles which are merely extended by others
+    /// (and are incomplete on their own) are fine. A profile which cannot be read
+    /// is skipped with a warning: it cannot be bumped anyway
+    pub fn load(ctx: &RTContext) -> CmdResult<Self> {
+        let profiles = ctx.profile_names()?;
+        let mut dependencies = LinkedHashMap::new();
+        for profile in profiles.iter() {
+            let declared = match declared_dependencies(ctx, profile) {
+                Ok(declared) => declared,
+                Err(err) => {
+                    if ctx.is_text_output() {
+                        println!(
+                            " {} Profile {} is skipped while looking for dependents: {}",
+                            "\u{26A0}".yellow(),
+                            profile.cyan(),
+                            err.description()
+                        );
+                    }
+                    continue;
+                }
+            };
+            dependencies.insert(profile.clone(), declared);
+        }
+        Ok(Graph {
+            dependencies,
+            profiles,
+        })
+    }
+
+    pub fn dependencies_of(&self, profile: &str) -> &[String] {
+        match self.dependencies.get(profile) {
+            Some(dependencies) => dependencies,
+            None => &[],
+        }
+    }
+
+    /// Bumped profiles and everything depending on them (transitively),
+    /// every profile goes after its dependencies
+    pub fn order(&self, bumped: &[String]) -> CmdResult<Vec<String>> {
+        let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (profile, dependencies) in self.dependencies.iter() {
+            for dependency in dependencies.iter() {
+                dependents.entry(dependency).or_default().push(profile);
+            }
+        }
+
+        let mut affected: Vec<&str> = self
+            .dependencies
+            .keys()
+            .filter(|profile| bumped.contains(profile))
+            .map(String::as_str)
+            .collect();
+        let mut index = 0;
+        while index < affected.len() {
+            for dependent in dependents.get(affected[index]).into_iter().flatten() {
+                if !affected.contains(dependent) {
+                    affected.push(dependent);
+                }
+            }
+            index += 1;
+        }
+
+        for profile in affected.iter() {
+            for dependency in self.dependencies_of(profile) {
+                if !self.profiles.contains(dependency) {
+                    return Err(Error::NoSuchDependency {
+                        profile: profile.to_string(),
+                        dependency: dependency.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut order = vec![];
+        let mut visited = HashMap::new();
+        for profile in affected.iter() {
+            self.visit(profile, &affected, &mut visited, &mut vec![], &mut order)?;
+        }
+        Ok(order)
+    }
+
+    /// Depth-first search putting dependencies first.
+    /// `visited` is false while the profile's dependencies are being visited
+    fn visit<'g>(
+        &'g self,
+        profile: &'g str,
+        affected: &[&str],
+        visited: &mut HashMap<&'g str, bool>,
+        path: &mut Vec<&'g str>,
+        order: &mut Vec<String>,
+    ) -> CmdResult {
+        match visited.get(profile) {
+            Some(true) => return Ok(()),
+            Some(false) => {
+                let start = path.iter().position(|name| *name == profile).unwrap_or(0);
+                let mut cycle: Vec<String> =
+                    path[start..].iter().map(|name| name.to_string()).collect();
+                cycle.push(profile.to_string());
+                return Err(Error::DependencyCycle { cycle });
+            }
+            None => {}
+        }
+        visited.insert(profile, false);
+        path.push(profile);
+        for dependency in self.dependencies_of(profile) {
+            if affected.contains(&dependency.as_str()) {
+                self.visit(dependency, affected, visited, path, order)?;
+            }
+        }
+        path.pop();
+        visited.insert(profile, true);
+        order.push(profile.to_string());
+        Ok(())
+    }
+}
+
+fn declared_dependencies(ctx: &RTContext, profile: &str) -> CmdResult<Vec<String>> {
+    let (project_ctx, name) = ctx.locate_profile(profile)?;
+    let composed = composition::compose(project_ctx, name)?;
+    Ok(
+        match composed
+            .merged
+            .get("dependencies")
+            .and_then(Item::as_table_like)
+        {
+            Some(declared) => declared
+                .iter()
+                .map(|(key, _item)| key.to_string())
+                .collect(),
+            None => vec![],
+        },
+    )
+}
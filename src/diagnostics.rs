@@ -0,0 +1,413 @@
+use std::path::{Path, PathBuf};
+
+use linked_hash_map::LinkedHashMap;
+use serde_derive::Deserialize;
+use toml::Spanned;
+
+use crate::config::{
+    Changelog, ConventionalCommits, Dependency, Factory, File, Git, Hooks, IntegerOrString,
+    ProfileConfig,
+};
+
+/// A problem in a profile's configuration pointing to its place in the file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    /// 1-based
+    pub line: usize,
+    /// 1-based, in characters
+    pub column: usize,
+    /// The whole line the problem is on
+    pub source_line: String,
+    /// How many characters of the line are underlined
+    pub width: usize,
+    /// File the problem is in if it's not the profile's own one
+    /// (or the profile is merged from several files)
+    pub path: Option<PathBuf>,
+}
+
+impl Diagnostic {
+    /// Point at `len` bytes starting from byte `offset` of `content`
+    pub fn at_offset(content: &str, offset: usize, len: usize, message: String) -> Self {
+        let offset = offset.min(content.len());
+        let line_start = content[..offset].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = content[offset..]
+            .find('\n')
+            .map_or(content.len(), |index| offset + index);
+        let source_line = content[line_start..line_end].trim_end_matches('\r');
+        let span_end = (offset + len).min(line_start + source_line.len());
+        Diagnostic {
+            message,
+            line: content[..line_start].matches('\n').count() + 1,
+            column: content[line_start..offset].chars().count() + 1,
+            source_line: source_line.to_string(),
+            width: content
+                .get(offset..span_end)
+                .map_or(1, |span| span.chars().count().max(1)),
+            path: None,
+        }
+    }
+
+    pub fn in_file(mut self, path: Option<&Path>) -> Self {
+        self.path = path.map(Path::to_path_buf);
+        self
+    }
+
+    /// Point where `toml` has failed to deserialize `content`
+    pub fn from_toml_error(content: &str, err: &toml::de::Error) -> Self {
+        let (line, column) = err.line_col().unwrap_or((0, 0));
+        Self::at_line_col(content, line, column, toml_message(err))
+    }
+
+    /// `line` and `column` are 0-based as `toml` reports them
+    fn at_line_col(content: &str, line: usize, column: usize, message: String) -> Self {
+        let line_start: usize = content
+            .split_inclusive('\n')
+            .take(line)
+            .map(|line| line.len())
+            .sum();
+        let offset = content[line_start..]
+            .char_indices()
+            .nth(column)
+            .map_or(content.len(), |(index, _)| line_start + index);
+        Self::at_offset(content, offset, 1, message)
+    }
+
+    /// Multiline text with the problematic line and the place underlined.
+    /// `path` is the profile's own file
+    pub fn render(&self, path: &Path) -> String {
+        let path = self.path.as_deref().unwrap_or(path);
+        let line_number = self.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        format!(
+            "{message}\n{gutter}--> {path}:{line}:{column}\n{gutter} |\n{line} | {source}\n{gutter} | {padding}{underline}",
+            message = self.message,
+            gutter = gutter,
+            path = path.display(),
+            line = line_number,
+            column = self.column,
+            source = self.source_line,
+            padding = " ".repeat(self.column - 1),
+            underline = "^".repeat(self.width),
+        )
+    }
+}
+
+/// Message of a `toml` error without its location (it's shown in the snippet)
+fn toml_message(err: &toml::de::Error) -> String {
+    let message = err.to_string();
+    match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message,
+    }
+}
+
+/// Deserialize a profile and check that it's consistent.
+/// All the found problems are returned at once
+pub fn check_profile(content: &str) -> Result<ProfileConfig, Vec<Diagnostic>> {
+    check_sources(content, &[(None, content)])
+}
+
+/// Check a profile merged from several files (in the merge order).
+/// Problems point to the file and the line the problematic value comes from
+pub fn check_merged_profile(
+    merged: &str,
+    sources: &[(&Path, &str)],
+) -> Result<ProfileConfig, Vec<Diagnostic>> {
+    if sources.len() <= 1 {
+        return check_profile(merged);
+    }
+    let sources: Vec<(Option<&Path>, &str)> = sources
+        .iter()
+        .map(|(path, content)| (Some(*path), *content))
+        .collect();
+    check_sources(merged, &sources)
+}
+
+/// Where a value is written
+struct Location<'a> {
+    path: Option<&'a Path>,
+    content: &'a str,
+    span: (usize, usize),
+}
+
+fn check_sources(
+    merged: &str,
+    sources: &[(Option<&Path>, &str)],
+) -> Result<ProfileConfig, Vec<Diagnostic>> {
+    let (own_path, own_content) = sources[sources.len() - 1];
+    let profile = match toml::from_str::<ProfileConfig>(merged) {
+        Ok(profile) => profile,
+        Err(err) if sources.len() == 1 => {
+            return Err(vec![Diagnostic::from_toml_error(merged, &err)])
+        }
+        Err(err) => {
+            // Values have the same types in every source, so such errors are found file by file
+            let diagnostics: Vec<Diagnostic> = sources
+                .iter()
+                .filter_map(
+                    |(path, content)| match toml::from_str::<PartialProfile>(content) {
+                        Ok(_partial) => None,
+                        Err(err) => Some(Diagnostic::from_toml_error(content, &err).in_file(*path)),
+                    },
+                )
+                .collect();
+            if !diagnostics.is_empty() {
+                return Err(diagnostics);
+            }
+            // Something is missing in all the sources together, i.e. a part's factory
+            return Err(vec![Diagnostic::at_offset(
+                own_content,
+                0,
+                0,
+                toml_message(&err),
+            )
+            .in_file(own_path)]);
+        }
+    };
+
+    // Same sources with spans of the values semantic checks point to
+    let located: Vec<(Option<&Path>, &str, LocatedProfile)> = sources
+        .iter()
+        .filter_map(|(path, content)| {
+            let located = toml::from_str::<LocatedProfile>(content).ok()?;
+            Some((*path, *content, located))
+        })
+        .collect();
+    // A value comes from the last source defining it
+    let locate = |find: &dyn Fn(&LocatedProfile) -> Option<(usize, usize)>| {
+        located.iter().rev().find_map(|(path, content, located)| {
+            find(located).map(|span| Location {
+                path: *path,
+                content,
+                span,
+            })
+        })
+    };
+    let point = |location: Option<Location>, message: String| match location {
+        Some(location) => Diagnostic::at_offset(
+            location.content,
+            location.span.0,
+            location.span.1 - location.span.0,
+            message,
+        )
+        .in_file(location.path),
+        None => Diagnostic::at_offset(own_content, 0, 0, message).in_file(own_path),
+    };
+
+    let mut diagnostics = vec![];
+    for (part_name, part) in profile.parts.iter() {
+        if let Factory::Loop(payload) = &part.factory {
+            if payload.is_empty() {
+                let location = locate(&|located| {
+                    Some(
+                        located
+                            .part(part_name)?
+                            .factory
+                            .as_ref()?
+                            .payload
+                            .as_ref()?
+                            .span(),
+                    )
+                })
+                .or_else(|| {
+                    locate(&|located| {
+                        Some(
+                            located
+                                .part(part_name)?
+                                .factory
+                                .as_ref()?
+                                .name
+                                .as_ref()?
+                                .span(),
+                        )
+                    })
+                });
+                diagnostics.push(point(
+                    location,
+                    format!("Loop factory payload of part `{}` is empty", part_name),
+                ));
+            } else if !payload.contains(&part.value) {
+                let location =
+                    locate(&|located| Some(located.part(part_name)?.value.as_ref()?.span()));
+                diagnostics.push(point(
+                    location,
+                    format!(
+                        "Value `{}` of part `{}` is not in its loop factory payload",
+                        part.value, part_name
+                    ),
+                ));
+            }
+        }
+    }
+
+    for (file_name, views) in profile.files.iter() {
+        for (index, file) in views.iter().enumerate() {
+            if file.enable_liquid_tempaltes.unwrap_or_default() {
+                continue;
+            }
+            let view = &file.version.view;
+            let location = locate(&|located| {
+                let file = located.files.as_ref()?.get(file_name)?.get(index)?;
+                Some(file.version.as_ref()?.view.span())
+            });
+            for (index, placeholder) in placeholders(view) {
+                if profile.parts.contains_key(placeholder) {
+                    continue;
+                }
+                let message = format!(
+                    "View of `{}` refers to `{{{}}}` but the profile has no such part",
+                    file_name, placeholder
+                );
+                let location = location.as_ref().map(|location| {
+                    let span = location.span;
+                    // Quotes are skipped if the view is written as is (i.e. has no escapes)
+                    let span = match location.content.get(span.0 + 1..) {
+                        Some(rest) if rest.starts_with(view.as_str()) => (
+                            span.0 + 1 + index,
+                            span.0 + 1 + index + placeholder.len() + 2,
+                        ),
+                        _ => span,
+                    };
+                    Location { span, ..*location }
+                });
+                diagnostics.push(point(location, message));
+            }
+        }
+    }
+
+    for (dependency_name, dependency) in profile.dependencies.iter().flatten() {
+        let part = match &dependency.bump {
+            Some(part) if !profile.parts.contains_key(part) => part,
+            _ => continue,
+        };
+        let location = locate(&|located| {
+            Some(
+                located
+                    .dependencies
+                    .as_ref()?
+                    .get(dependency_name)?
+                    .bump
+                    .as_ref()?
+                    .span(),
+            )
+        });
+        diagnostics.push(point(
+            location,
+            format!(
+                "Dependency `{}` bumps `{}` but the profile has no such part",
+                dependency_name, part
+            ),
+        ));
+    }
+
+    if let Some(git) = &profile.git {
+        // Otherwise the tag would label the commit made before the bump
+        if git.tag.is_some() && !git.commit.unwrap_or_default() {
+            let location = locate(&|located| Some(located.git.as_ref()?.tag.as_ref()?.span()));
+            diagnostics.push(point(
+                location,
+                "`git.tag` requires `git.commit = true`".to_string(),
+            ));
+        }
+    }
+
+    if diagnostics.is_empty() {
+        Ok(profile)
+    } else {
+        Err(diagnostics)
+    }
+}
+
+/// `{part}` placeholders of a view with their byte positions
+pub fn placeholders(view: &str) -> Vec<(usize, &str)> {
+    let mut found = vec![];
+    for (start, _) in view.match_indices('{') {
+        let rest = &view[start + 1..];
+        if let Some(end) = rest.find('}') {
+            let name = &rest[..end];
+            let is_part_name = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+            if is_part_name {
+                found.push((start, name));
+            }
+        }
+    }
+    found
+}
+
+/// A source of a merged profile: anything may be defined in another source
+#[derive(Deserialize)]
+struct PartialProfile {
+    #[serde(rename = "parts")]
+    _parts: Option<LinkedHashMap<String, PartialPart>>,
+    #[serde(rename = "files")]
+    _files: Option<LinkedHashMap<String, Vec<File>>>,
+    #[serde(rename = "hooks")]
+    _hooks: Option<Hooks>,
+    #[serde(rename = "git")]
+    _git: Option<Git>,
+    #[serde(rename = "conventional_commits")]
+    _conventional_commits: Option<ConventionalCommits>,
+    #[serde(rename = "changelog")]
+    _changelog: Option<Changelog>,
+    #[serde(rename = "dependencies")]
+    _dependencies: Option<LinkedHashMap<String, Dependency>>,
+}
+
+#[derive(Deserialize)]
+struct PartialPart {
+    #[serde(rename = "value")]
+    _value: Option<IntegerOrString<u64>>,
+    #[serde(rename = "factory")]
+    _factory: Option<Factory>,
+}
+
+/// Spans of values of a source. Anything may be defined in another source
+#[derive(Deserialize)]
+struct LocatedProfile {
+    parts: Option<LinkedHashMap<String, LocatedPart>>,
+    files: Option<LinkedHashMap<String, Vec<LocatedFile>>>,
+    dependencies: Option<LinkedHashMap<String, LocatedDependency>>,
+    git: Option<LocatedGit>,
+}
+
+impl LocatedProfile {
+    fn part(&self, name: &str) -> Option<&LocatedPart> {
+        self.parts.as_ref()?.get(name)
+    }
+}
+
+#[derive(Deserialize)]
+struct LocatedPart {
+    value: Option<Spanned<IntegerOrString<u64>>>,
+    factory: Option<LocatedFactory>,
+}
+
+#[derive(Deserialize)]
+struct LocatedFactory {
+    name: Option<Spanned<String>>,
+    payload: Option<Spanned<toml::Value>>,
+}
+
+#[derive(Deserialize)]
+struct LocatedFile {
+    version: Option<LocatedFileVersion>,
+}
+
+#[derive(Deserialize)]
+struct LocatedFileVersion {
+    view: Spanned<String>,
+}
+
+#[derive(Deserialize)]
+struct LocatedDependency {
+    bump: Option<Spanned<String>>,
+}
+
+#[derive(Deserialize)]
+struct LocatedGit {
+    tag: Option<Spanned<String>>,
+}
@@ -1,28 +1,1032 @@
+use std::path::PathBuf;
+
+use crate::context::HookStage;
+use crate::diagnostics::Diagnostic;
+
+/// Everything that can go wrong in weee. Variants keep the profile,
+/// paths and the underlying error so callers can inspect them,
+/// the CLI renders them with `title`, `description` and `payload`
 #[derive(Debug)]
-pub struct CLIError {
-    pub title: &'static str,
-    pub description: &'static str,
-    pub payload: Vec<(&'static str, String)>,
+pub enum Error {
+    // Project and profiles
+    WeeeDirectoryAlreadyExists {
+        path: PathBuf,
+    },
+    CannotCreateWeeeDirectory {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    ProfileAlreadyExists {
+        profile: String,
+        path: PathBuf,
+    },
+    CannotCreateProfileRule {
+        profile: String,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    NoSuchProfileExists {
+        profile: String,
+        path: PathBuf,
+    },
+    TOMLInvalidSyntax {
+        profile: String,
+        path: PathBuf,
+        source: toml_edit::TomlError,
+    },
+    CannotWriteToProfileFile {
+        profile: String,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    CannotReadProfile {
+        profile: String,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// Profile does not match its schema or is inconsistent
+    InvalidProfileConfig {
+        profile: String,
+        path: PathBuf,
+        diagnostics: Vec<Diagnostic>,
+        /// Files the profile is merged from
+        sources: Vec<PathBuf>,
+    },
+    /// `weee validate` has found problems (they are already reported)
+    ValidationFailed {
+        profiles: Vec<String>,
+        problems: usize,
+    },
+
+    // Profile composition
+    ProfileInheritanceCycle {
+        profile: String,
+        /// Files in the order they refer to each other
+        cycle: Vec<String>,
+    },
+
+    // Workspace
+    CannotReadWorkspaceFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    InvalidWorkspaceFile {
+        path: PathBuf,
+        diagnostics: Vec<Diagnostic>,
+    },
+    /// `<member>:<profile>` refers to a member the workspace does not list
+    NoSuchWorkspaceMember {
+        member: String,
+        members: Vec<String>,
+    },
+
+    // Profile groups
+    CannotReadGroupsFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    InvalidGroupsFile {
+        path: PathBuf,
+        diagnostics: Vec<Diagnostic>,
+    },
+    NoSuchProfileGroup {
+        group: String,
+        groups: Vec<String>,
+    },
+    NoProfileMatchesPattern {
+        pattern: String,
+    },
+
+    // Profile dependencies
+    NoSuchDependency {
+        profile: String,
+        dependency: String,
+    },
+    DependencyCycle {
+        /// Profiles in the order they depend on each other
+        cycle: Vec<String>,
+    },
+
+    // Version parts
+    NoSuchVersionPartExists {
+        profile: String,
+        part: String,
+    },
+    CannotParsePartValueToInteger {
+        profile: String,
+        part: String,
+        value: String,
+        source: std::num::ParseIntError,
+    },
+    LoopFactoryPayloadIsEmpty {
+        profile: String,
+        part: String,
+    },
+    CurrentValueOfLoopedPartDoesNotExist {
+        profile: String,
+        part: String,
+        value: String,
+    },
+
+    // Files and their views
+    NoSuchFileForReplacements {
+        profile: String,
+        path: PathBuf,
+    },
+    CannotReadReplacementsFileContent {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    FileDoesNotContainOldVersion {
+        profile: String,
+        file: String,
+        old_match: String,
+    },
+    NotEnoughOldVersionMatches {
+        profile: String,
+        file: String,
+        old_match: String,
+        expected: u64,
+        found: u64,
+    },
+    CannotWriteToFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    NoSuchFileView {
+        profile: String,
+        file: String,
+        nth: usize,
+    },
+    ViewCannotBeParsed {
+        profile: String,
+        file: String,
+        view: String,
+    },
+    ChangelogHasNoUnreleasedSection {
+        profile: String,
+        file: String,
+        heading: String,
+    },
+
+    // Templates
+    InvalidTemplateSyntax {
+        profile: String,
+        /// File or option the template comes from
+        origin: String,
+        template: String,
+        source: liquid::Error,
+    },
+    LiquidTemplateRuntimeError {
+        profile: String,
+        origin: String,
+        template: String,
+        source: liquid::Error,
+    },
+
+    // Hooks
+    CannotExecuteSubprocess {
+        profile: String,
+        hook: String,
+        stage: HookStage,
+        source: std::io::Error,
+    },
+    SubproccessCallFailed {
+        profile: String,
+        hook: String,
+        stage: HookStage,
+        attempts: u32,
+        /// `None` if the process has been killed by a signal
+        exit_code: Option<i32>,
+        stderr: String,
+    },
+    HookTimedOut {
+        profile: String,
+        hook: String,
+        stage: HookStage,
+        attempts: u32,
+        timeout_secs: u64,
+        stderr: String,
+    },
+    InvalidHookDefinition {
+        profile: String,
+        hook: String,
+        stage: HookStage,
+        reason: &'static str,
+    },
+    CannotCreateHookLog {
+        profile: String,
+        hook: String,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    // Terminal interaction
+    CannotFlushStdout {
+        source: std::io::Error,
+    },
+    CannotReadNewValueFromStdin {
+        source: std::io::Error,
+    },
+    /// Stdin is closed (or is not a terminal for an editor)
+    /// and the asked value has no default
+    InputIsNotAvailable {
+        prompt: String,
+    },
+    CannotRunEditor {
+        editor: String,
+        path: PathBuf,
+        /// Set if the editor has been run but failed
+        status: Option<std::process::ExitStatus>,
+        source: Option<std::io::Error>,
+    },
+    NoSuchEnvironmentVariable {
+        variable: String,
+        source: std::env::VarError,
+    },
+    CannotReadArgumentFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    // Git
+    GitCommandFailed {
+        command: String,
+        stderr: String,
+        /// Set if git cannot be executed at all
+        source: Option<std::io::Error>,
+    },
+    WorkingTreeIsDirty {
+        files: Vec<String>,
+    },
+    GitTagAlreadyExists {
+        profile: String,
+        tag: String,
+    },
+    NoMatchingGitTag {
+        profile: String,
+        pattern: String,
+    },
+    NoCommitsToRelease {
+        profile: String,
+        since_tag: Option<String>,
+        commits: usize,
+    },
+    GitTagIsNotUnique {
+        tag: String,
+        /// Profiles bumped together which render the tag
+        profiles: Vec<String>,
+    },
+    DifferentGitRepositories {
+        /// Profiles bumped together with their repositories' roots
+        profiles: Vec<(String, PathBuf)>,
+    },
+
+    // History
+    CannotReadHistory {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    CannotWriteHistory {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    NothingToUndo,
+    VersionChangedSinceBump {
+        profile: String,
+        expected: String,
+        current: String,
+    },
+    FileChangedSinceBump {
+        profile: String,
+        file: String,
+    },
+    InvalidHistoryEntry {
+        path: PathBuf,
+        line: usize,
+        source: serde_json::Error,
+    },
+
+    /// On-failure hooks have failed too while handling `error`.
+    /// Everything except the payload is taken from `error`
+    OnFailureHooksFailed {
+        error: Box<Error>,
+        on_failure_error: Box<Error>,
+    },
 }
 
-#[macro_export]
-macro_rules! show_err {
-    ([$title:ident] => $description:expr, $( $key:ident = $value:expr ),* $(,)?) => {
-        {
-            let payload = vec![
-                $(
-                    (stringify!($key), $value.to_string()),
-                )*
-            ];
-            Err(CLIError {
-                title: stringify!($title),
-                description: $description,
+impl Error {
+    /// Name of the variant, stable across releases
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::WeeeDirectoryAlreadyExists { .. } => "WeeeDirectoryAlreadyExists",
+            Self::CannotCreateWeeeDirectory { .. } => "CannotCreateWeeeDirectory",
+            Self::ProfileAlreadyExists { .. } => "ProfileAlreadyExists",
+            Self::CannotCreateProfileRule { .. } => "CannotCreateProfileRule",
+            Self::NoSuchProfileExists { .. } => "NoSuchProfileExists",
+            Self::TOMLInvalidSyntax { .. } => "TOMLInvalidSyntax",
+            Self::CannotWriteToProfileFile { .. } => "CannotWriteToProfileFile",
+            Self::CannotReadProfile { .. } => "CannotReadProfile",
+            Self::InvalidProfileConfig { .. } => "InvalidProfileConfig",
+            Self::ValidationFailed { .. } => "ValidationFailed",
+            Self::ProfileInheritanceCycle { .. } => "ProfileInheritanceCycle",
+            Self::CannotReadWorkspaceFile { .. } => "CannotReadWorkspaceFile",
+            Self::InvalidWorkspaceFile { .. } => "InvalidWorkspaceFile",
+            Self::NoSuchWorkspaceMember { .. } => "NoSuchWorkspaceMember",
+            Self::CannotReadGroupsFile { .. } => "CannotReadGroupsFile",
+            Self::InvalidGroupsFile { .. } => "InvalidGroupsFile",
+            Self::NoSuchProfileGroup { .. } => "NoSuchProfileGroup",
+            Self::NoProfileMatchesPattern { .. } => "NoProfileMatchesPattern",
+            Self::NoSuchDependency { .. } => "NoSuchDependency",
+            Self::DependencyCycle { .. } => "DependencyCycle",
+            Self::NoSuchVersionPartExists { .. } => "NoSuchVersionPartExists",
+            Self::CannotParsePartValueToInteger { .. } => "CannotParsePartValueToInteger",
+            Self::LoopFactoryPayloadIsEmpty { .. } => "LoopFactoryPayloadIsEmpty",
+            Self::CurrentValueOfLoopedPartDoesNotExist { .. } => {
+                "CurrentValueOfLoopedPartDoesNotExist"
+            }
+            Self::NoSuchFileForReplacements { .. } => "NoSuchFileForReplacements",
+            Self::CannotReadReplacementsFileContent { .. } => "CannotReadReplacementsFileContent",
+            Self::FileDoesNotContainOldVersion { .. } => "FileDoesNotContainOldVersion",
+            Self::NotEnoughOldVersionMatches { .. } => "NotEnoughOldVersionMatches",
+            Self::CannotWriteToFile { .. } => "CannotWriteToFile",
+            Self::NoSuchFileView { .. } => "NoSuchFileView",
+            Self::ViewCannotBeParsed { .. } => "ViewCannotBeParsed",
+            Self::ChangelogHasNoUnreleasedSection { .. } => "ChangelogHasNoUnreleasedSection",
+            Self::InvalidTemplateSyntax { .. } => "InvalidTemplateSyntax",
+            Self::LiquidTemplateRuntimeError { .. } => "LiquidTemplateRuntimeError",
+            Self::CannotExecuteSubprocess { .. } => "CannotExecuteSubprocess",
+            Self::SubproccessCallFailed { .. } => "SubproccessCallFailed",
+            Self::HookTimedOut { .. } => "HookTimedOut",
+            Self::InvalidHookDefinition { .. } => "InvalidHookDefinition",
+            Self::CannotCreateHookLog { .. } => "CannotCreateHookLog",
+            Self::CannotFlushStdout { .. } => "CannotFlushStdout",
+            Self::CannotReadNewValueFromStdin { .. } => "CannotReadNewValueFromStdin",
+            Self::InputIsNotAvailable { .. } => "InputIsNotAvailable",
+            Self::CannotRunEditor { .. } => "CannotRunEditor",
+            Self::NoSuchEnvironmentVariable { .. } => "NoSuchEnvironmentVariable",
+            Self::CannotReadArgumentFile { .. } => "CannotReadArgumentFile",
+            Self::GitCommandFailed { .. } => "GitCommandFailed",
+            Self::WorkingTreeIsDirty { .. } => "WorkingTreeIsDirty",
+            Self::GitTagAlreadyExists { .. } => "GitTagAlreadyExists",
+            Self::NoMatchingGitTag { .. } => "NoMatchingGitTag",
+            Self::NoCommitsToRelease { .. } => "NoCommitsToRelease",
+            Self::GitTagIsNotUnique { .. } => "GitTagIsNotUnique",
+            Self::DifferentGitRepositories { .. } => "DifferentGitRepositories",
+            Self::CannotReadHistory { .. } => "CannotReadHistory",
+            Self::CannotWriteHistory { .. } => "CannotWriteHistory",
+            Self::NothingToUndo => "NothingToUndo",
+            Self::VersionChangedSinceBump { .. } => "VersionChangedSinceBump",
+            Self::FileChangedSinceBump { .. } => "FileChangedSinceBump",
+            Self::InvalidHistoryEntry { .. } => "InvalidHistoryEntry",
+            Self::OnFailureHooksFailed { error, .. } => error.title(),
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::WeeeDirectoryAlreadyExists { .. } => {
+                "Weee tool has been already initialized. It's ready to use"
+            }
+            Self::CannotCreateWeeeDirectory { .. } => {
+                "An OS error occured while creating a .weee directory"
+            }
+            Self::ProfileAlreadyExists { .. } => "Such version profile config already exists",
+            Self::CannotCreateProfileRule { .. } => {
+                "An OS error occured while creating rule for the profile"
+            }
+            Self::NoSuchProfileExists { .. } => {
+                "No such profile exists. Check out whether you typed scope wrongly or deleted the profile file"
+            }
+            Self::TOMLInvalidSyntax { .. } => "Invalid syntax in a profile configuration file",
+            Self::CannotWriteToProfileFile { .. } => {
+                "An OS error accured while writing to profile file"
+            }
+            Self::CannotReadProfile { .. } => "An OS error occured while reading the profile",
+            Self::InvalidProfileConfig { .. } => "Profile configuration is invalid",
+            Self::ValidationFailed { .. } => "Some of the profiles have problems",
+            Self::ProfileInheritanceCycle { .. } => {
+                "Profile extends or includes itself through other files"
+            }
+            Self::CannotReadWorkspaceFile { .. } => {
+                "An OS error occured while reading the workspace file"
+            }
+            Self::InvalidWorkspaceFile { .. } => "Workspace file is invalid",
+            Self::NoSuchWorkspaceMember { .. } => "Workspace has no such member",
+            Self::CannotReadGroupsFile { .. } => {
+                "An OS error occured while reading the profile groups file"
+            }
+            Self::InvalidGroupsFile { .. } => "Profile groups file is invalid",
+            Self::NoSuchProfileGroup { .. } => "Such profile group does not exist",
+            Self::NoProfileMatchesPattern { .. } => "No profile matches the pattern",
+            Self::NoSuchDependency { .. } => "Profile depends on a profile which does not exist",
+            Self::DependencyCycle { .. } => "Profiles depend on each other",
+            Self::NoSuchVersionPartExists { .. } => "Such version part does not exist",
+            Self::CannotParsePartValueToInteger { .. } => {
+                "Version part value is not a valid integer"
+            }
+            Self::LoopFactoryPayloadIsEmpty { .. } => "Loop factory payload cannot be empty",
+            Self::CurrentValueOfLoopedPartDoesNotExist { .. } => {
+                "Version part managed by a loop factory with no such part in payload"
+            }
+            Self::NoSuchFileForReplacements { .. } => "No such file to make version replacements",
+            Self::CannotReadReplacementsFileContent { .. } => "Cannot read file's content",
+            Self::FileDoesNotContainOldVersion { .. } => {
+                "Changed files has no old version in it's content"
+            }
+            Self::NotEnoughOldVersionMatches { .. } => {
+                "Count of old version entries is not as supposed"
+            }
+            Self::CannotWriteToFile { .. } => "Cannot write new version into file",
+            Self::NoSuchFileView { .. } => {
+                "Profile has no such file or the file has not so many views"
+            }
+            Self::ViewCannotBeParsed { .. } => "Version cannot be parsed with a liquid view",
+            Self::ChangelogHasNoUnreleasedSection { .. } => {
+                "Changelog has no section with unreleased changes"
+            }
+            Self::InvalidTemplateSyntax { .. } => "Invalid liquid template syntax",
+            Self::LiquidTemplateRuntimeError { .. } => {
+                "An error occured while rendering a template"
+            }
+            Self::CannotExecuteSubprocess { .. } => "An error occured while executing subproccess",
+            Self::SubproccessCallFailed { .. } => "Subproccess has exited with an error",
+            Self::HookTimedOut { .. } => "Subproccess has not finished in time and was killed",
+            Self::InvalidHookDefinition { .. } => "Hook's definition is invalid",
+            Self::CannotCreateHookLog { .. } => {
+                "An OS error occured while creating a log file for the hook"
+            }
+            Self::CannotFlushStdout { .. } => "Cannot flush stdout",
+            Self::CannotReadNewValueFromStdin { .. } => "Cannot get an input from stdin",
+            Self::InputIsNotAvailable { .. } => {
+                "Input is not available (stdin is closed or is not a terminal) and the value has no default"
+            }
+            Self::CannotRunEditor { status: Some(_), .. } => "Editor exited with an error",
+            Self::CannotRunEditor { .. } => "Cannot run the editor",
+            Self::NoSuchEnvironmentVariable { .. } => {
+                "Environment variable used in a hook is not set"
+            }
+            Self::CannotReadArgumentFile { .. } => "Cannot read a file used as a hook argument",
+            Self::GitCommandFailed {
+                source: Some(_), ..
+            } => "Cannot execute git. Check it's installed",
+            Self::GitCommandFailed { .. } => "Git command has failed",
+            Self::WorkingTreeIsDirty { .. } => {
+                "Working tree has changes besides the files managed by the profile. Commit or stash them first"
+            }
+            Self::GitTagAlreadyExists { .. } => "Such git tag already exists",
+            Self::NoMatchingGitTag { .. } => {
+                "There is no git tag matching the pattern and the profile's view"
+            }
+            Self::NoCommitsToRelease { .. } => {
+                "No commits since the last tag require a version bump"
+            }
+            Self::GitTagIsNotUnique { .. } => {
+                "Profiles bumped together render the same git tag"
+            }
+            Self::DifferentGitRepositories { .. } => {
+                "Profiles bumped together with the git integration are in different repositories"
+            }
+            Self::CannotReadHistory { .. } => "Cannot read the history file",
+            Self::CannotWriteHistory { .. } => "Cannot append an entry to the history file",
+            Self::NothingToUndo => "History has no bumps or moves to undo",
+            Self::VersionChangedSinceBump { .. } => {
+                "Profile's version has changed since the change to undo"
+            }
+            Self::FileChangedSinceBump { .. } => {
+                "File has changed since the change to undo. Revert it manually"
+            }
+            Self::InvalidHistoryEntry { .. } => "History file has an invalid entry",
+            Self::OnFailureHooksFailed { error, .. } => error.description(),
+        }
+    }
+
+    /// Details of the error as key-value pairs
+    pub fn payload(&self) -> Vec<(&'static str, String)> {
+        fn path(path: &std::path::Path) -> String {
+            path.display().to_string()
+        }
+
+        match self {
+            Self::WeeeDirectoryAlreadyExists { path: dir } => vec![("path", path(dir))],
+            Self::CannotCreateWeeeDirectory { path: dir, source } => {
+                vec![("path", path(dir)), ("os_error", source.to_string())]
+            }
+            Self::ProfileAlreadyExists {
+                profile,
+                path: file,
+            }
+            | Self::NoSuchProfileExists {
+                profile,
+                path: file,
+            } => vec![("profile", profile.clone()), ("path", path(file))],
+            Self::CannotCreateProfileRule {
+                profile,
+                path: file,
+                source,
+            }
+            | Self::CannotWriteToProfileFile {
+                profile,
+                path: file,
+                source,
+            }
+            | Self::CannotReadProfile {
+                profile,
+                path: file,
+                source,
+            } => vec![
+                ("profile", profile.clone()),
+                ("path", path(file)),
+                ("os_error", source.to_string()),
+            ],
+            Self::InvalidProfileConfig {
+                profile,
+                path: file,
+                diagnostics,
+                sources,
+            } => {
+                let mut payload = vec![("profile", profile.clone()), ("path", path(file))];
+                if sources.len() > 1 {
+                    let sources: Vec<String> = sources.iter().map(|source| path(source)).collect();
+                    payload.push(("merged_from", sources.join(", ")));
+                }
+                payload.push((
+                    "errors",
+                    diagnostics
+                        .iter()
+                        .map(|diagnostic| diagnostic.render(file))
+                        .collect::<Vec<_>>()
+                        .join("\n\n"),
+                ));
+                payload
+            }
+            Self::ProfileInheritanceCycle { profile, cycle } => {
+                vec![("profile", profile.clone()), ("cycle", cycle.join(" -> "))]
+            }
+            Self::CannotReadWorkspaceFile { path: file, source }
+            | Self::CannotReadGroupsFile { path: file, source } => {
+                vec![("path", path(file)), ("os_error", source.to_string())]
+            }
+            Self::InvalidWorkspaceFile {
+                path: file,
+                diagnostics,
+            }
+            | Self::InvalidGroupsFile {
+                path: file,
+                diagnostics,
+            } => vec![
+                ("path", path(file)),
+                (
+                    "errors",
+                    diagnostics
+                        .iter()
+                        .map(|diagnostic| diagnostic.render(file))
+                        .collect::<Vec<_>>()
+                        .join("\n\n"),
+                ),
+            ],
+            Self::NoSuchWorkspaceMember { member, members } => {
+                vec![("member", member.clone()), ("members", members.join(", "))]
+            }
+            Self::NoSuchProfileGroup { group, groups } => {
+                vec![("group", group.clone()), ("groups", groups.join(", "))]
+            }
+            Self::NoProfileMatchesPattern { pattern } => vec![("pattern", pattern.clone())],
+            Self::NoSuchDependency {
+                profile,
+                dependency,
+            } => vec![
+                ("profile", profile.clone()),
+                ("dependency", dependency.clone()),
+            ],
+            Self::DependencyCycle { cycle } => vec![("cycle", cycle.join(" -> "))],
+            Self::ValidationFailed { profiles, problems } => vec![
+                ("profiles", profiles.join(", ")),
+                ("problems", problems.to_string()),
+            ],
+            Self::TOMLInvalidSyntax {
+                profile,
+                path: file,
+                source,
+            } => vec![
+                ("profile", profile.clone()),
+                ("path", path(file)),
+                ("error", source.to_string()),
+            ],
+            Self::NoSuchVersionPartExists { profile, part }
+            | Self::LoopFactoryPayloadIsEmpty { profile, part } => {
+                vec![("profile", profile.clone()), ("part", part.clone())]
+            }
+            Self::CannotParsePartValueToInteger {
+                profile,
+                part,
+                value,
+                source,
+            } => vec![
+                ("profile", profile.clone()),
+                ("part", part.clone()),
+                ("value", value.clone()),
+                ("error", source.to_string()),
+            ],
+            Self::CurrentValueOfLoopedPartDoesNotExist {
+                profile,
+                part,
+                value,
+            } => vec![
+                ("profile", profile.clone()),
+                ("part", part.clone()),
+                ("value", value.clone()),
+            ],
+            Self::NoSuchFileForReplacements {
+                profile,
+                path: file,
+            } => vec![("profile", profile.clone()), ("path", path(file))],
+            Self::CannotReadReplacementsFileContent { path: file, source }
+            | Self::CannotWriteToFile { path: file, source }
+            | Self::CannotReadArgumentFile { path: file, source }
+            | Self::CannotReadHistory { path: file, source }
+            | Self::CannotWriteHistory { path: file, source } => {
+                vec![("path", path(file)), ("os_error", source.to_string())]
+            }
+            Self::FileDoesNotContainOldVersion {
+                profile,
+                file,
+                old_match,
+            } => vec![
+                ("profile", profile.clone()),
+                ("file", file.clone()),
+                ("old_match", old_match.clone()),
+            ],
+            Self::NotEnoughOldVersionMatches {
+                profile,
+                file,
+                old_match,
+                expected,
+                found,
+            } => vec![
+                ("profile", profile.clone()),
+                ("file", file.clone()),
+                ("old_match", old_match.clone()),
+                ("expected", expected.to_string()),
+                ("found", found.to_string()),
+            ],
+            Self::NoSuchFileView { profile, file, nth } => vec![
+                ("profile", profile.clone()),
+                ("file", file.clone()),
+                ("nth", nth.to_string()),
+            ],
+            Self::ViewCannotBeParsed {
+                profile,
+                file,
+                view,
+            } => vec![
+                ("profile", profile.clone()),
+                ("file", file.clone()),
+                ("view", view.clone()),
+            ],
+            Self::ChangelogHasNoUnreleasedSection {
+                profile,
+                file,
+                heading,
+            } => vec![
+                ("profile", profile.clone()),
+                ("file", file.clone()),
+                ("heading", heading.clone()),
+            ],
+            Self::InvalidTemplateSyntax {
+                profile,
+                origin,
+                template,
+                source,
+            }
+            | Self::LiquidTemplateRuntimeError {
+                profile,
+                origin,
+                template,
+                source,
+            } => vec![
+                ("profile", profile.clone()),
+                ("origin", origin.clone()),
+                ("template", template.clone()),
+                ("error", source.to_string()),
+            ],
+            Self::CannotExecuteSubprocess {
+                profile,
+                hook,
+                stage,
+                source,
+            } => vec![
+                ("profile", profile.clone()),
+                ("command", hook.clone()),
+                ("stage", stage.name().to_string()),
+                ("error", source.to_string()),
+            ],
+            Self::SubproccessCallFailed {
+                profile,
+                hook,
+                stage,
+                attempts,
+                exit_code,
+                stderr,
+            } => vec![
+                ("profile", profile.clone()),
+                ("command", hook.clone()),
+                ("stage", stage.name().to_string()),
+                ("attempts", attempts.to_string()),
+                (
+                    "exit_code",
+                    exit_code.map_or("<killed by signal>".to_string(), |code| code.to_string()),
+                ),
+                ("stderr", stderr.clone()),
+            ],
+            Self::HookTimedOut {
+                profile,
+                hook,
+                stage,
+                attempts,
+                timeout_secs,
+                stderr,
+            } => vec![
+                ("profile", profile.clone()),
+                ("command", hook.clone()),
+                ("stage", stage.name().to_string()),
+                ("timeout_secs", timeout_secs.to_string()),
+                ("attempts", attempts.to_string()),
+                ("stderr", stderr.clone()),
+            ],
+            Self::InvalidHookDefinition {
+                profile,
+                hook,
+                stage,
+                reason,
+            } => vec![
+                ("reason", reason.to_string()),
+                ("profile", profile.clone()),
+                ("command", hook.clone()),
+                ("stage", stage.name().to_string()),
+            ],
+            Self::CannotCreateHookLog {
+                profile,
+                hook,
+                path: file,
+                source,
+            } => vec![
+                ("profile", profile.clone()),
+                ("command", hook.clone()),
+                ("path", path(file)),
+                ("os_error", source.to_string()),
+            ],
+            Self::CannotFlushStdout { source } | Self::CannotReadNewValueFromStdin { source } => {
+                vec![("os_error", source.to_string())]
+            }
+            Self::InputIsNotAvailable { prompt } => vec![("prompt", prompt.clone())],
+            Self::CannotRunEditor {
+                editor,
+                path: file,
+                status,
+                source,
+            } => {
+                let mut payload = vec![("editor", editor.clone()), ("path", path(file))];
+                if let Some(status) = status {
+                    payload.push(("status", status.to_string()));
+                }
+                if let Some(source) = source {
+                    payload.push(("os_error", source.to_string()));
+                }
                 payload
-            })
+            }
+            Self::NoSuchEnvironmentVariable { variable, source } => vec![
+                ("variable", variable.clone()),
+                ("error", source.to_string()),
+            ],
+            Self::GitCommandFailed {
+                command,
+                stderr,
+                source,
+            } => match source {
+                Some(source) => vec![
+                    ("command", command.clone()),
+                    ("os_error", source.to_string()),
+                ],
+                None => vec![("command", command.clone()), ("stderr", stderr.clone())],
+            },
+            Self::WorkingTreeIsDirty { files } => vec![("files", files.join(", "))],
+            Self::GitTagAlreadyExists { profile, tag } => {
+                vec![("profile", profile.clone()), ("tag", tag.clone())]
+            }
+            Self::NoMatchingGitTag { profile, pattern } => {
+                vec![("profile", profile.clone()), ("pattern", pattern.clone())]
+            }
+            Self::NoCommitsToRelease {
+                profile,
+                since_tag,
+                commits,
+            } => vec![
+                ("profile", profile.clone()),
+                (
+                    "since_tag",
+                    since_tag.clone().unwrap_or_else(|| "<no tag>".into()),
+                ),
+                ("commits", commits.to_string()),
+            ],
+            Self::GitTagIsNotUnique { tag, profiles } => {
+                vec![("tag", tag.clone()), ("profiles", profiles.join(", "))]
+            }
+            Self::DifferentGitRepositories { profiles } => profiles
+                .iter()
+                .map(|(profile, repository)| {
+                    (
+                        "repository",
+                        format!("{} ({})", repository.display(), profile),
+                    )
+                })
+                .collect(),
+            Self::NothingToUndo => vec![],
+            Self::VersionChangedSinceBump {
+                profile,
+                expected,
+                current,
+            } => vec![
+                ("profile", profile.clone()),
+                ("expected", expected.clone()),
+                ("current", current.clone()),
+            ],
+            Self::FileChangedSinceBump { profile, file } => {
+                vec![("profile", profile.clone()), ("file", file.clone())]
+            }
+            Self::InvalidHistoryEntry {
+                path: file,
+                line,
+                source,
+            } => vec![
+                ("path", path(file)),
+                ("line", line.to_string()),
+                ("error", source.to_string()),
+            ],
+            Self::OnFailureHooksFailed {
+                error,
+                on_failure_error,
+            } => {
+                let mut payload = error.payload();
+                payload.push((
+                    "on_failure_hooks_error",
+                    format!(
+                        "{}: {}",
+                        on_failure_error.title(),
+                        on_failure_error.description()
+                    ),
+                ));
+                payload
+            }
         }
-    };
+    }
+
+    /// Process exit code for the error. Codes are grouped by the error's nature
+    /// and must stay the same across releases because scripts rely on them.
+    /// `2` is taken by clap for invalid arguments
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            // Project and profiles
+            Self::WeeeDirectoryAlreadyExists { .. } => 10,
+            Self::CannotCreateWeeeDirectory { .. } => 11,
+            Self::ProfileAlreadyExists { .. } => 12,
+            Self::CannotCreateProfileRule { .. } => 13,
+            Self::NoSuchProfileExists { .. } => 14,
+            Self::TOMLInvalidSyntax { .. } => 15,
+            Self::CannotWriteToProfileFile { .. } => 16,
+            Self::CannotReadProfile { .. } => 17,
+            Self::InvalidProfileConfig { .. } => 18,
+            Self::ValidationFailed { .. } => 19,
+
+            // Version parts
+            Self::NoSuchVersionPartExists { .. } => 20,
+            Self::CannotParsePartValueToInteger { .. } => 21,
+            Self::LoopFactoryPayloadIsEmpty { .. } => 22,
+            Self::CurrentValueOfLoopedPartDoesNotExist { .. } => 23,
+
+            // Files and their views
+            Self::NoSuchFileForReplacements { .. } => 30,
+            Self::CannotReadReplacementsFileContent { .. } => 31,
+            Self::FileDoesNotContainOldVersion { .. } => 32,
+            Self::NotEnoughOldVersionMatches { .. } => 33,
+            Self::CannotWriteToFile { .. } => 34,
+            Self::NoSuchFileView { .. } => 35,
+            Self::ViewCannotBeParsed { .. } => 36,
+            Self::ChangelogHasNoUnreleasedSection { .. } => 37,
+
+            // Templates
+            Self::InvalidTemplateSyntax { .. } => 40,
+            Self::LiquidTemplateRuntimeError { .. } => 41,
+
+            // Hooks
+            Self::CannotExecuteSubprocess { .. } => 50,
+            Self::SubproccessCallFailed { .. } => 51,
+            Self::HookTimedOut { .. } => 52,
+            Self::InvalidHookDefinition { .. } => 53,
+            Self::CannotCreateHookLog { .. } => 54,
+
+            // Terminal interaction
+            Self::CannotFlushStdout { .. } => 60,
+            Self::CannotReadNewValueFromStdin { .. } => 61,
+            Self::InputIsNotAvailable { .. } => 62,
+            Self::CannotRunEditor { .. } => 63,
+            Self::NoSuchEnvironmentVariable { .. } => 64,
+            Self::CannotReadArgumentFile { .. } => 65,
+
+            // Git
+            Self::GitCommandFailed { .. } => 70,
+            Self::WorkingTreeIsDirty { .. } => 71,
+            Self::GitTagAlreadyExists { .. } => 72,
+            Self::NoMatchingGitTag { .. } => 73,
+            Self::NoCommitsToRelease { .. } => 74,
+            Self::GitTagIsNotUnique { .. } => 75,
+            Self::DifferentGitRepositories { .. } => 76,
+
+            // History
+            Self::CannotReadHistory { .. } => 80,
+            Self::CannotWriteHistory { .. } => 81,
+            Self::NothingToUndo => 82,
+            Self::VersionChangedSinceBump { .. } => 83,
+            Self::FileChangedSinceBump { .. } => 84,
+            Self::InvalidHistoryEntry { .. } => 85,
+
+            // Profile composition
+            Self::ProfileInheritanceCycle { .. } => 90,
+
+            // Workspace
+            Self::CannotReadWorkspaceFile { .. } => 91,
+            Self::InvalidWorkspaceFile { .. } => 92,
+            Self::NoSuchWorkspaceMember { .. } => 93,
 
-    ([$title:ident] => $description:expr) => {
-        show_err!([$title] => $description,)
-    };
+            // Profile groups
+            Self::CannotReadGroupsFile { .. } => 100,
+            Self::InvalidGroupsFile { .. } => 101,
+            Self::NoSuchProfileGroup { .. } => 102,
+            Self::NoProfileMatchesPattern { .. } => 103,
+
+            // Profile dependencies
+            Self::NoSuchDependency { .. } => 110,
+            Self::DependencyCycle { .. } => 111,
+
+            Self::OnFailureHooksFailed { error, .. } => error.exit_code(),
+        }
+    }
+
+    /// Machine-readable representation of the error
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut payload = serde_json::Map::new();
+        for (key, value) in self.payload() {
+            payload.insert(key.to_string(), value.into());
+        }
+        serde_json::json!({
+            "title": self.title(),
+            "description": self.description(),
+            "payload": payload,
+            "exit_code": self.exit_code(),
+        })
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.title(), self.description())
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::CannotCreateWeeeDirectory { source, .. }
+            | Self::CannotCreateProfileRule { source, .. }
+            | Self::CannotWriteToProfileFile { source, .. }
+            | Self::CannotReadProfile { source, .. }
+            | Self::CannotReadReplacementsFileContent { source, .. }
+            | Self::CannotWriteToFile { source, .. }
+            | Self::CannotExecuteSubprocess { source, .. }
+            | Self::CannotCreateHookLog { source, .. }
+            | Self::CannotFlushStdout { source }
+            | Self::CannotReadNewValueFromStdin { source }
+            | Self::CannotReadArgumentFile { source, .. }
+            | Self::CannotReadHistory { source, .. }
+            | Self::CannotWriteHistory { source, .. }
+            | Self::CannotReadWorkspaceFile { source, .. }
+            | Self::CannotReadGroupsFile { source, .. } => Some(source),
+            Self::CannotRunEditor {
+                source: Some(source),
+                ..
+            }
+            | Self::GitCommandFailed {
+                source: Some(source),
+                ..
+            } => Some(source),
+            Self::TOMLInvalidSyntax { source, .. } => Some(source),
+            Self::CannotParsePartValueToInteger { source, .. } => Some(source),
+            Self::InvalidTemplateSyntax { source, .. }
+            | Self::LiquidTemplateRuntimeError { source, .. } => Some(source),
+            Self::NoSuchEnvironmentVariable { source, .. } => Some(source),
+            Self::InvalidHistoryEntry { source, .. } => Some(source),
+            Self::OnFailureHooksFailed { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
 }
@@ -0,0 +1,256 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+use crate::handleable::CmdResult;
+
+/// Thin wrapper around the `git` executable working with a local repository only
+pub struct Repository {
+    /// Directory git is executed in (the project's root)
+    workdir: PathBuf,
+    /// Root of the repository's working tree
+    toplevel: PathBuf,
+}
+
+impl Repository {
+    pub fn open(workdir: &Path) -> CmdResult<Self> {
+        let toplevel = run(workdir, &["rev-parse", "--show-toplevel"])?;
+        Ok(Repository {
+            workdir: workdir.to_path_buf(),
+            toplevel: PathBuf::from(toplevel.trim_end()),
+        })
+    }
+
+    /// Root of the repository's working tree
+    pub fn toplevel(&self) -> &Path {
+        &self.toplevel
+    }
+
+    /// Changed, staged and untracked files (ignored ones are skipped)
+    /// as absolute paths
+    pub fn changed_files(&self) -> CmdResult<Vec<PathBuf>> {
+        let status = run(&self.workdir, &["status", "--porcelain", "-z"])?;
+        let mut files = vec![];
+        let mut entries = status.split('\0').filter(|entry| !entry.is_empty());
+        while let Some(entry) = entries.next() {
+            let (code, path) = entry.split_at(3.min(entry.len()));
+            files.push(self.toplevel.join(path));
+            // Renames and copies are followed by the original path
+            if code.starts_with('R') || code.starts_with('C') {
+                entries.next();
+            }
+        }
+        Ok(files)
+    }
+
+    /// Fail if there are changed files other than `allowed` ones
+    /// (an allowed directory allows everything inside it)
+    pub fn ensure_clean(&self, allowed: &[PathBuf]) -> CmdResult {
+        let allowed: Vec<PathBuf> = allowed.iter().map(|path| normalize(path)).collect();
+        let unexpected: Vec<String> = self
+            .changed_files()?
+            .iter()
+            .filter(|path| {
+                let path = normalize(path);
+                !allowed.iter().any(|allowed| path.starts_with(allowed))
+            })
+            .map(|path| {
+                path.strip_prefix(&self.toplevel)
+                    .unwrap_or(path)
+                    .display()
+                    .to_string()
+            })
+            .collect();
+        if !unexpected.is_empty() {
+            return Err(Error::WorkingTreeIsDirty { files: unexpected });
+        }
+        Ok(())
+    }
+
+    /// Tags matching a glob pattern like `v*`
+    pub fn tags(&self, pattern: &str) -> CmdResult<Vec<String>> {
+        let tags = run(&self.workdir, &["tag", "--list", pattern])?;
+        Ok(tags.lines().map(String::from).collect())
+    }
+
+    /// The nearest tag reachable from `HEAD` matching a glob pattern
+    pub fn last_tag(&self, pattern: &str) -> CmdResult<Option<String>> {
+        // `describe` fails if no such tag is reachable from `HEAD`
+        // (i.e. on a branch made before the first release), so check it in advance
+        let reachable = run(
+            &self.workdir,
+            &["tag", "--merged", "HEAD", "--list", pattern],
+        )?;
+        if reachable.trim().is_empty() {
+            return Ok(None);
+        }
+        let tag = run(
+            &self.workdir,
+            &["describe", "--tags", "--abbrev=0", "--match", pattern],
+        )?;
+        Ok(Some(tag.trim_end().to_string()))
+    }
+
+    /// Commits reachable from `HEAD` but not from `since`, newest first
+    pub fn commits_since(&self, since: Option<&str>) -> CmdResult<Vec<Commit>> {
+        let range = match since {
+            Some(since) => format!("{}..HEAD", since),
+            None => "HEAD".into(),
+        };
+        // Fields are separated with the unit separator, commits with the record one
+        let log = run(&self.workdir, &["log", "--format=%H%x1f%B%x1e", &range])?;
+        Ok(log
+            .split('\x1e')
+            .filter_map(|record| {
+                let (hash, message) = record.trim_start().split_once('\x1f')?;
+                Some(Commit {
+                    hash: hash.to_string(),
+                    message: message.trim_end().to_string(),
+                })
+            })
+            .collect())
+    }
+
+    pub fn tag_exists(&self, tag: &str) -> CmdResult<bool> {
+        let tags = run(&self.workdir, &["tag", "--list", tag])?;
+        Ok(tags.lines().any(|line| line == tag))
+    }
+
+    /// Commit only the given files (whatever else is staged is kept staged)
+    /// and return the new commit's hash
+    pub fn commit(&self, files: &[PathBuf], message: &str) -> CmdResult<String> {
+        // Paths are relative to the current directory, not to the one git is executed in
+        let files: Vec<PathBuf> = files.iter().map(|path| normalize(path)).collect();
+        let files: Vec<&str> = files.iter().filter_map(|path| path.to_str()).collect();
+        run(&self.workdir, &[&["add", "--"], files.as_slice()].concat())?;
+        run(
+            &self.workdir,
+            &[
+                &["commit", "--quiet", "-m", message, "--"],
+                files.as_slice(),
+            ]
+            .concat(),
+        )?;
+        Ok(run(&self.workdir, &["rev-parse", "HEAD"])?
+            .trim_end()
+            .to_string())
+    }
+
+    /// Drop the last commit made by `commit`, its files are unstaged
+    /// but their contents in the working tree are kept
+    pub fn reset_last_commit(&self, files: &[PathBuf]) -> CmdResult {
+        let files: Vec<PathBuf> = files.iter().map(|path| normalize(path)).collect();
+        let files: Vec<&str> = files.iter().filter_map(|path| path.to_str()).collect();
+        run(&self.workdir, &["reset", "--soft", "--quiet", "HEAD^"])?;
+        run(
+            &self.workdir,
+            &[&["reset", "--quiet", "--"], files.as_slice()].concat(),
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_tag(&self, tag: &str) -> CmdResult {
+        run(&self.workdir, &["tag", "--delete", tag])?;
+        Ok(())
+    }
+
+    /// Create an annotated tag if a message is given, otherwise a lightweight one
+    pub fn tag(&self, tag: &str, message: Option<&str>) -> CmdResult {
+        match message {
+            Some(message) => run(&self.workdir, &["tag", "--annotate", tag, "-m", message])?,
+            None => run(&self.workdir, &["tag", tag])?,
+        };
+        Ok(())
+    }
+}
+
+pub struct Commit {
+    pub hash: String,
+    pub message: String,
+}
+
+/// Header of a Conventional Commit: `type(scope)!: description`
+pub struct ConventionalHeader {
+    /// Lowercased type
+    pub commit_type: String,
+    pub scope: Option<String>,
+    pub description: String,
+    pub breaking: bool,
+}
+
+impl Commit {
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or_default()
+    }
+
+    /// `None` if the commit does not follow Conventional Commits
+    pub fn conventional(&self) -> Option<ConventionalHeader> {
+        let (header, description) = self.subject().split_once(':')?;
+        let (header, bang) = match header.strip_suffix('!') {
+            Some(header) => (header, true),
+            None => (header, false),
+        };
+        let (commit_type, scope) = match header.split_once('(') {
+            Some((commit_type, scope)) => (commit_type, Some(scope.strip_suffix(')')?)),
+            None => (header, None),
+        };
+        if commit_type.is_empty()
+            || !commit_type
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return None;
+        }
+        let breaking_footer = self.message.lines().skip(1).any(|line| {
+            line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+        });
+        Some(ConventionalHeader {
+            commit_type: commit_type.to_ascii_lowercase(),
+            scope: scope.map(String::from),
+            description: description.trim().to_string(),
+            breaking: bang || breaking_footer,
+        })
+    }
+}
+
+/// Absolute path without `.` and `..` components. Files may not exist,
+/// so `canonicalize` cannot be used for all of them
+fn normalize(path: &Path) -> PathBuf {
+    let absolute = match path.canonicalize() {
+        Ok(path) => return path,
+        Err(_err) if path.is_absolute() => path.to_path_buf(),
+        Err(_err) => std::env::current_dir().unwrap_or_default().join(path),
+    };
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+fn run(workdir: &Path, args: &[&str]) -> CmdResult<String> {
+    let output = std::process::Command::new("git")
+        .args(args)
+        .current_dir(workdir)
+        .output();
+    match output {
+        Ok(output) if output.status.success() => {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        }
+        Ok(output) => Err(Error::GitCommandFailed {
+            command: format!("git {}", args.join(" ")),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+            source: None,
+        }),
+        Err(err) => Err(Error::GitCommandFailed {
+            command: format!("git {}", args.join(" ")),
+            stderr: String::new(),
+            source: Some(err),
+        }),
+    }
+}
@@ -0,0 +1,46 @@
+//! Named lists of profiles bumped in one transaction (`weee bump --group release patch`):
+//!
+//! ```toml
+//! # .weee/groups.toml
+//! release = ["project", "sdk"]
+//! packages = ["packages/api:project", "packages/cli:project"]
+//! libraries = ["packages/*:lib"]
+//! ```
+//!
+//! `*` and `?` match within a segment of a member's path or a profile's name.
+
+use std::path::Path;
+
+use linked_hash_map::LinkedHashMap;
+
+use crate::diagnostics::Diagnostic;
+use crate::error::Error;
+use crate::handleable::CmdResult;
+
+pub const GROUPS_FILE: &str = "groups.toml";
+
+/// Profiles of the group in the listed order
+pub fn profiles(root: &Path, group: &str) -> CmdResult<Vec<String>> {
+    let path = root.join(".weee").join(GROUPS_FILE);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(Error::CannotReadGroupsFile { path, source: err }),
+    };
+    let mut groups = match toml::from_str::<LinkedHashMap<String, Vec<String>>>(&content) {
+        Ok(groups) => groups,
+        Err(err) => {
+            return Err(Error::InvalidGroupsFile {
+                diagnostics: vec![Diagnostic::from_toml_error(&content, &err)],
+                path,
+            })
+        }
+    };
+    match groups.remove(group) {
+        Some(profiles) => Ok(profiles),
+        None => Err(Error::NoSuchProfileGroup {
+            group: group.to_string(),
+            groups: groups.keys().cloned().collect(),
+        }),
+    }
+}
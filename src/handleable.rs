@@ -1,7 +1,7 @@
 use crate::context::RTContext;
-use crate::error::CLIError;
+use crate::error::Error;
 
-pub type CmdResult<S = ()> = Result<S, CLIError>;
+pub type CmdResult<S = ()> = Result<S, Error>;
 
 pub trait Handleable {
     fn handle(&self, ctx: &mut RTContext) -> CmdResult;
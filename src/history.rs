@@ -0,0 +1,175 @@
+use std::io::Write;
+use std::path::Path;
+
+use serde_derive::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::context::{HookStage, Version};
+use crate::error::Error;
+use crate::handleable::CmdResult;
+use crate::report::VersionReport;
+
+/// What has been done to a profile
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Bump,
+    Move,
+    Undo,
+}
+
+impl Action {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Bump => "bump",
+            Action::Move => "move",
+            Action::Undo => "undo",
+        }
+    }
+}
+
+/// One line of `.weee/history.jsonl` of the workspace's root
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    /// UTC time in RFC 3339
+    pub timestamp: String,
+    pub action: Action,
+    /// `<member>:<profile>` for profiles of workspace members
+    pub profile: String,
+    /// Made in one transaction with the previous entry (i.e. profiles bumped together),
+    /// such entries are undone together
+    #[serde(default)]
+    pub with_previous: bool,
+    /// Only references to dependencies have been rewritten, the version is kept
+    #[serde(default)]
+    pub references_only: bool,
+    /// Dependencies bumped in the same transaction whose references have been rewritten
+    #[serde(default)]
+    pub dependencies: Vec<HistoryDependency>,
+    pub bumped_part: Option<String>,
+    pub old: VersionReport,
+    pub new: VersionReport,
+    /// Files as they are right after the change
+    pub files: Vec<HistoryFile>,
+    pub changelog: Option<HistoryChangelog>,
+    pub hooks: Vec<HistoryHook>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryDependency {
+    pub profile: String,
+    pub old: Version,
+    pub new: Version,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryFile {
+    /// Relative to the project's root
+    pub path: String,
+    pub sha256: String,
+}
+
+/// How the "Unreleased" section has been promoted, so it can be reverted
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryChangelog {
+    pub path: String,
+    pub unreleased: String,
+    /// Text inserted right after the "Unreleased" heading
+    pub inserted: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryHook {
+    pub name: String,
+    pub stage: HookStage,
+    pub success: bool,
+}
+
+pub fn timestamp() -> String {
+    let now = liquid::model::DateTime::now();
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        now.year(),
+        now.month(),
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.second()
+    )
+}
+
+pub fn hash_file(path: &Path) -> CmdResult<String> {
+    match std::fs::read(path) {
+        Ok(content) => Ok(format!("{:x}", Sha256::digest(content))),
+        Err(err) => Err(Error::CannotReadReplacementsFileContent {
+            path: path.to_path_buf(),
+            source: err,
+        }),
+    }
+}
+
+/// All entries, oldest first. No history file means no entries
+pub fn load(path: &Path) -> CmdResult<Vec<HistoryEntry>> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => {
+            return Err(Error::CannotReadHistory {
+                path: path.to_path_buf(),
+                source: err,
+            })
+        }
+    };
+
+    let mut entries = vec![];
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(err) => {
+                return Err(Error::InvalidHistoryEntry {
+                    path: path.to_path_buf(),
+                    line: index + 1,
+                    source: err,
+                })
+            }
+        }
+    }
+    Ok(entries)
+}
+
+pub fn append(path: &Path, entry: &HistoryEntry) -> CmdResult {
+    let result = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", serde_json::to_string(entry).unwrap()));
+    if let Err(err) = result {
+        return Err(Error::CannotWriteHistory {
+            path: path.to_path_buf(),
+            source: err,
+        });
+    }
+    Ok(())
+}
+
+/// Entries of the latest bump or move transaction which has not been undone yet
+pub fn last_undoable(entries: &[HistoryEntry]) -> Option<Vec<&HistoryEntry>> {
+    let mut stack: Vec<Vec<&HistoryEntry>> = vec![];
+    for entry in entries {
+        match (entry.action, stack.last_mut()) {
+            (Action::Bump | Action::Move, Some(transaction)) if entry.with_previous => {
+                transaction.push(entry)
+            }
+            (Action::Bump | Action::Move, _) => stack.push(vec![entry]),
+            // A transaction is undone with as many entries, the first one pops it
+            (Action::Undo, _) if entry.with_previous => {}
+            (Action::Undo, _) => {
+                stack.pop();
+            }
+        }
+    }
+    stack.pop()
+}
@@ -0,0 +1,39 @@
+//! Advanced version bumper for any project.
+//!
+//! The `weee` binary is a thin layer over this crate, so release tooling
+//! and build scripts can run the same operations and get reports back:
+//!
+//! ```no_run
+//! use weee::{BumpOperation, RTContext};
+//!
+//! let ctx = RTContext::at("path/to/project");
+//! let report = BumpOperation::new("project", "minor").run(&ctx).unwrap();
+//! println!("{} -> {}", report.old.version, report.new.version);
+//! ```
+//!
+//! Contexts created with [`RTContext::at`] print nothing,
+//! everything is returned in [`report::Report`]s and [`error::Error`]s.
+
+pub mod commands;
+mod composition;
+pub mod config;
+pub mod context;
+mod dependencies;
+pub mod diagnostics;
+pub mod error;
+mod git;
+mod groups;
+pub mod handleable;
+pub mod history;
+pub mod operations;
+mod process;
+pub mod report;
+pub mod schema;
+mod tests;
+mod validation;
+mod workspace;
+
+pub use context::{ChangedFile, ChangedVersion, ProfileContext, RTContext, Version};
+pub use operations::{
+    BumpOperation, CheckOperation, MoveOperation, MultiBumpOperation, ValidateOperation,
+};
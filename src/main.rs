@@ -1,13 +1,6 @@
 use clap::Parser;
 
-mod commands;
-mod config;
-mod context;
-mod error;
-mod handleable;
-mod tests;
-
 fn main() {
-    let args = commands::CLI::parse();
-    args.handle();
+    let args = weee::commands::CLI::parse();
+    std::process::exit(args.handle());
 }
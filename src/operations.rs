@@ -0,0 +1,524 @@
+use colored::Colorize;
+use linked_hash_map::LinkedHashMap;
+
+use crate::config::IntegerOrString;
+use crate::context::{
+    apply_changed_versions, ChangedVersion, ProfileChange, ProfileContext, RTContext, Version,
+};
+use crate::dependencies;
+use crate::handleable::CmdResult;
+use crate::report::{AutoReport, FileReport, Report, ValidationReport};
+use crate::validation;
+
+/// Bump a part of the profile's version to the next generation
+/// and apply it to the project (`weee bump`). Profiles depending on it
+/// are changed in the same transaction and reported in `Report::dependents`
+#[derive(Debug, Clone)]
+pub struct BumpOperation {
+    profile: String,
+    part: String,
+    read_only: bool,
+}
+
+impl BumpOperation {
+    /// `auto` picks the part from Conventional Commits made since the last
+    /// version tag (unless the profile has an `auto` part)
+    pub fn new<P: Into<String>, S: Into<String>>(profile: P, part: S) -> Self {
+        BumpOperation {
+            profile: profile.into(),
+            part: part.into(),
+            read_only: false,
+        }
+    }
+
+    /// Do not change anything, only report what would be changed
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn run(&self, ctx: &RTContext) -> CmdResult<Report> {
+        let profile_ctx = ctx.fetch_profile_conext(&self.profile)?;
+        let (part, auto_report) = pick_part(ctx, &profile_ctx, &self.part)?;
+
+        let changed_version = profile_ctx.bump_version(&part)?;
+        let mut report = Report::new(&profile_ctx, Some(&part), &changed_version, self.read_only);
+        report.auto = auto_report;
+
+        if ctx.is_text_output() {
+            println!(
+                " \u{1F389} Weee! Bumping {} ({} -> {})",
+                part.cyan(),
+                changed_version.old[&part].to_string().red(),
+                changed_version.new[&part].to_string().green(),
+            );
+        }
+        let bump = ProfileBump {
+            profile_ctx,
+            part: Some(part),
+            references_only: false,
+            changed_version,
+            report,
+            dependencies: vec![],
+        };
+        let mut reports = bump_with_dependents(ctx, vec![bump], self.read_only)?;
+        let mut report = reports.remove(0);
+        report.dependents = reports;
+        Ok(report)
+    }
+}
+
+/// The requested part or the one `auto` picks from Conventional Commits
+fn pick_part(
+    ctx: &RTContext,
+    profile_ctx: &ProfileContext,
+    part: &str,
+) -> CmdResult<(String, Option<AutoReport>)> {
+    if part == "auto" && !profile_ctx.profile_model.parts.contains_key("auto") {
+        let auto_report = profile_ctx.auto_bump_part()?;
+        if ctx.is_text_output() {
+            print_reasoning(&auto_report);
+        }
+        Ok((auto_report.part.clone(), Some(auto_report)))
+    } else {
+        Ok((part.to_string(), None))
+    }
+}
+
+fn print_reasoning(auto_report: &AutoReport) {
+    match &auto_report.since_tag {
+        Some(tag) => println!(" \u{1F50E} Commits since {}:", tag.cyan()),
+        None => println!(" \u{1F50E} No version tags found, looking at all commits:"),
+    }
+    for commit in auto_report.commits.iter() {
+        let part = match &commit.part {
+            Some(part) => part.green(),
+            None => "-".bright_black(),
+        };
+        println!(
+            "    {} {} {}",
+            commit.hash[..7.min(commit.hash.len())].yellow(),
+            part,
+            commit.subject
+        );
+    }
+    println!(
+        " \u{1F449} The most significant part is {}",
+        auto_report.part.cyan()
+    );
+}
+
+/// Bump the same part of several profiles in one transaction
+/// (`weee bump --profiles` or `--group`). Nothing is changed unless every profile
+/// can be bumped, files shared by profiles are written once, the git integration
+/// makes one commit with every tag and hooks shared by profiles are executed once.
+/// Profiles depending on them are changed in the same transaction
+#[derive(Debug, Clone)]
+pub struct MultiBumpOperation {
+    profiles: ProfileSelection,
+    part: String,
+    read_only: bool,
+}
+
+#[derive(Debug, Clone)]
+enum ProfileSelection {
+    Profiles(Vec<String>),
+    /// Group from `.weee/groups.toml`
+    Group(String),
+}
+
+impl MultiBumpOperation {
+    /// `auto` picks a part for every profile separately
+    pub fn new<I, P, S>(profiles: I, part: S) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<String>,
+        S: Into<String>,
+    {
+        MultiBumpOperation {
+            profiles: ProfileSelection::Profiles(profiles.into_iter().map(Into::into).collect()),
+            part: part.into(),
+            read_only: false,
+        }
+    }
+
+    /// Profiles listed in `.weee/groups.toml` under the name
+    pub fn group<G: Into<String>, S: Into<String>>(group: G, part: S) -> Self {
+        MultiBumpOperation {
+            profiles: ProfileSelection::Group(group.into()),
+            part: part.into(),
+            read_only: false,
+        }
+    }
+
+    /// Do not change anything, only report what would be changed
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Reports of the profiles in the order they are listed followed by their dependents
+    pub fn run(&self, ctx: &RTContext) -> CmdResult<Vec<Report>> {
+        let listed = match &self.profiles {
+            ProfileSelection::Profiles(profiles) => profiles.clone(),
+            ProfileSelection::Group(group) => ctx.profile_group(group)?,
+        };
+        let mut profiles: Vec<String> = vec![];
+        for profile in ctx.expand_profile_patterns(&listed)? {
+            if !profiles.contains(&profile) {
+                profiles.push(profile);
+            }
+        }
+
+        let mut bumps = vec![];
+        for profile in profiles.iter() {
+            let profile_ctx = ctx.fetch_profile_conext(profile)?;
+            let (part, auto_report) = pick_part(ctx, &profile_ctx, &self.part)?;
+            let changed_version = profile_ctx.bump_version(&part)?;
+            let mut report =
+                Report::new(&profile_ctx, Some(&part), &changed_version, self.read_only);
+            report.auto = auto_report;
+
+            if ctx.is_text_output() {
+                println!(
+                    " \u{1F389} Weee! Bumping {} of {} ({} -> {})",
+                    part.cyan(),
+                    report.profile.cyan(),
+                    changed_version.old[&part].to_string().red(),
+                    changed_version.new[&part].to_string().green(),
+                );
+            }
+            bumps.push(ProfileBump {
+                profile_ctx,
+                part: Some(part),
+                references_only: false,
+                changed_version,
+                report,
+                dependencies: vec![],
+            });
+        }
+        bump_with_dependents(ctx, bumps, self.read_only)
+    }
+}
+
+/// A profile's share of a bump with its dependents
+struct ProfileBump<'rtctx> {
+    profile_ctx: ProfileContext<'rtctx>,
+    /// `None` for a move or if only references to dependencies are rewritten
+    part: Option<String>,
+    references_only: bool,
+    changed_version: ChangedVersion,
+    report: Report,
+    /// Bumped dependencies, by name
+    dependencies: Vec<String>,
+}
+
+/// Add profiles depending on the bumped (or moved) ones (transitively, dependencies first)
+/// and apply everything as one transaction. A dependent gets the most significant
+/// part its bumped dependencies ask for, or only its references are rewritten.
+/// Reports are in the order of bumps followed by the dependents
+fn bump_with_dependents<'rtctx>(
+    ctx: &'rtctx RTContext,
+    mut bumps: Vec<ProfileBump<'rtctx>>,
+    read_only: bool,
+) -> CmdResult<Vec<Report>> {
+    let graph = dependencies::Graph::load(ctx)?;
+    let mut names: Vec<String> = bumps
+        .iter()
+        .map(|bump| bump.profile_ctx.qualified_name())
+        .collect();
+
+    for profile in graph.order(&names)? {
+        let bumped_dependencies: Vec<String> = graph
+            .dependencies_of(&profile)
+            .iter()
+            .filter(|dependency| {
+                names
+                    .iter()
+                    .position(|name| name == *dependency)
+                    .is_some_and(|index| !bumps[index].references_only)
+            })
+            .cloned()
+            .collect();
+        if let Some(index) = names.iter().position(|name| *name == profile) {
+            bumps[index].dependencies = bumped_dependencies;
+            continue;
+        }
+        if bumped_dependencies.is_empty() {
+            continue;
+        }
+        bumps.push(bump_dependent(
+            ctx,
+            &profile,
+            bumped_dependencies,
+            read_only,
+        )?);
+        names.push(profile);
+    }
+
+    let mut profile_ctxs = vec![];
+    let mut parts = vec![];
+    let mut references_only = vec![];
+    let mut versions = vec![];
+    let mut reports = vec![];
+    let mut dependencies = vec![];
+    for bump in bumps {
+        profile_ctxs.push(bump.profile_ctx);
+        parts.push(bump.part);
+        references_only.push(bump.references_only);
+        versions.push(bump.changed_version);
+        reports.push(bump.report);
+        dependencies.push(bump.dependencies);
+    }
+    let mut changes: Vec<ProfileChange> = profile_ctxs
+        .iter_mut()
+        .zip(reports.iter_mut())
+        .enumerate()
+        .map(|(index, (profile_ctx, report))| ProfileChange {
+            profile_ctx,
+            changed_version: &versions[index],
+            bumped_part: parts[index].as_deref(),
+            dependencies: dependencies[index]
+                .iter()
+                .filter_map(|dependency| {
+                    let position = names.iter().position(|name| name == dependency)?;
+                    Some((dependency.as_str(), &versions[position]))
+                })
+                .collect(),
+            references_only: references_only[index],
+            report,
+        })
+        .collect();
+    apply_changed_versions(&mut changes, read_only)?;
+    Ok(reports)
+}
+
+/// A dependent of bumped profiles. The most significant part
+/// (the first in the profile) of those the dependencies ask for is bumped
+fn bump_dependent<'rtctx>(
+    ctx: &'rtctx RTContext,
+    profile: &str,
+    dependencies: Vec<String>,
+    read_only: bool,
+) -> CmdResult<ProfileBump<'rtctx>> {
+    let profile_ctx = ctx.fetch_profile_conext(profile)?;
+    let config = &profile_ctx.profile_model;
+    let mut chosen: Option<(usize, String)> = None;
+    for dependency in dependencies.iter() {
+        let requested = config
+            .dependencies
+            .as_ref()
+            .and_then(|declared| declared.get(dependency))
+            .and_then(|declared| declared.bump.as_ref());
+        if let Some(requested) = requested {
+            profile_ctx.check_part_exists(requested)?;
+            let position = config
+                .parts
+                .keys()
+                .position(|part_name| part_name == requested)
+                .unwrap_or_default();
+            if chosen
+                .as_ref()
+                .is_none_or(|(chosen, _part)| position < *chosen)
+            {
+                chosen = Some((position, requested.clone()));
+            }
+        }
+    }
+    let part = chosen.map(|(_position, part)| part);
+
+    let changed_version = match &part {
+        Some(part) => profile_ctx.bump_version(part)?,
+        None => {
+            let current_version = profile_ctx.current_version();
+            ChangedVersion {
+                old: current_version.clone(),
+                new: current_version,
+            }
+        }
+    };
+    let report = Report::new(&profile_ctx, part.as_deref(), &changed_version, read_only);
+
+    if ctx.is_text_output() {
+        match &part {
+            Some(part) => println!(
+                " \u{1F517} {} depends on {}, bumping {} ({} -> {})",
+                report.profile.cyan(),
+                dependencies.join(", ").cyan(),
+                part.cyan(),
+                changed_version.old[part].to_string().red(),
+                changed_version.new[part].to_string().green(),
+            ),
+            None => println!(
+                " \u{1F517} {} depends on {}, updating references",
+                report.profile.cyan(),
+                dependencies.join(", ").cyan(),
+            ),
+        }
+    }
+    Ok(ProfileBump {
+        profile_ctx,
+        references_only: part.is_none(),
+        part,
+        changed_version,
+        report,
+        dependencies,
+    })
+}
+
+/// Move the profile's version to custom values and apply it to the project (`weee move`).
+/// Profiles depending on it are changed the same way as with `BumpOperation`
+#[derive(Debug, Clone)]
+pub struct MoveOperation {
+    profile: String,
+    parts: Version,
+    interactive: bool,
+    read_only: bool,
+}
+
+impl MoveOperation {
+    /// Parts which are not set keep their current values
+    pub fn new<P: Into<String>>(profile: P) -> Self {
+        MoveOperation {
+            profile: profile.into(),
+            parts: LinkedHashMap::new(),
+            interactive: false,
+            read_only: false,
+        }
+    }
+
+    pub fn part<S: Into<String>>(mut self, name: S, value: IntegerOrString<u64>) -> Self {
+        self.parts.insert(name.into(), value);
+        self
+    }
+
+    /// Ask for every part's value in the terminal instead of using `part`s
+    pub fn interactive(mut self, interactive: bool) -> Self {
+        self.interactive = interactive;
+        self
+    }
+
+    /// Do not change anything, only report what would be changed
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    pub fn run(&self, ctx: &RTContext) -> CmdResult<Report> {
+        let profile_ctx = ctx.fetch_profile_conext(&self.profile)?;
+        let text_output = ctx.is_text_output();
+        let old_version = profile_ctx.current_version();
+
+        let new_version = if self.interactive {
+            if text_output {
+                println!(" \u{1F389} Weee! Bumping to custom version. Let's decide how the new version should look like!");
+            }
+            let new_version = profile_ctx.ask_another_version()?;
+            if text_output {
+                println!();
+            }
+            new_version
+        } else {
+            for part_name in self.parts.keys() {
+                profile_ctx.check_part_exists(part_name)?;
+            }
+            old_version
+                .iter()
+                .map(|(part_name, value)| {
+                    let value = self.parts.get(part_name).unwrap_or(value);
+                    (part_name.clone(), value.clone())
+                })
+                .collect()
+        };
+
+        if text_output {
+            if self.read_only {
+                println!(" \u{1F44D} Well, now going to do changes (read-only)...");
+            } else {
+                println!(" \u{1F44D} Well, now going to do changes...");
+            }
+        }
+
+        let changed_version = ChangedVersion {
+            new: new_version,
+            old: old_version,
+        };
+        let report = Report::new(&profile_ctx, None, &changed_version, self.read_only);
+        let moved = ProfileBump {
+            profile_ctx,
+            part: None,
+            references_only: false,
+            changed_version,
+            report,
+            dependencies: vec![],
+        };
+        let mut reports = bump_with_dependents(ctx, vec![moved], self.read_only)?;
+        let mut report = reports.remove(0);
+        report.dependents = reports;
+        Ok(report)
+    }
+}
+
+/// Check that every file contains the profile's current version
+/// the way the profile describes it. Nothing is changed
+#[derive(Debug, Clone)]
+pub struct CheckOperation {
+    profile: String,
+}
+
+impl CheckOperation {
+    pub fn new<P: Into<String>>(profile: P) -> Self {
+        CheckOperation {
+            profile: profile.into(),
+        }
+    }
+
+    /// Where the current version has been found in every file
+    pub fn run(&self, ctx: &RTContext) -> CmdResult<Vec<FileReport>> {
+        let profile_ctx = ctx.fetch_profile_conext(&self.profile)?;
+        let current_version = profile_ctx.current_version();
+        let changed_version = ChangedVersion {
+            old: current_version.clone(),
+            new: current_version,
+        };
+        let prepared_changed_files = profile_ctx.prepare_replacemts(&changed_version)?;
+        profile_ctx.change_files_content(&prepared_changed_files, true)
+    }
+}
+
+/// Run every static check of profiles' configuration (`weee validate`).
+/// Nothing is changed and no hook is executed
+#[derive(Debug, Clone)]
+pub struct ValidateOperation {
+    /// `None` means all the profiles
+    profile: Option<String>,
+}
+
+impl ValidateOperation {
+    pub fn new<P: Into<String>>(profile: P) -> Self {
+        ValidateOperation {
+            profile: Some(profile.into()),
+        }
+    }
+
+    pub fn all() -> Self {
+        ValidateOperation { profile: None }
+    }
+
+    /// Problems of every checked profile. Found problems are not errors
+    pub fn run(&self, ctx: &RTContext) -> CmdResult<Vec<ValidationReport>> {
+        let profiles = match &self.profile {
+            Some(profile) => vec![profile.clone()],
+            None => ctx.profile_names()?,
+        };
+        let mut reports = vec![];
+        for profile in profiles {
+            let (project_ctx, name) = ctx.locate_profile(&profile)?;
+            reports.push(ValidationReport {
+                problems: validation::validate_profile(project_ctx, name)?,
+                profile,
+            });
+        }
+        Ok(reports)
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use colored::Colorize;
+
+/// How often a running process is checked for exit when a timeout is set
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// How many last lines of each stream are kept for reports and errors
+pub const TAIL_LINES: usize = 100;
+
+pub struct ProcessOutput {
+    /// `None` if the process has been killed because of the timeout
+    pub status: Option<ExitStatus>,
+    /// Last `TAIL_LINES` lines of stdout
+    pub stdout: String,
+    /// Last `TAIL_LINES` lines of stderr
+    pub stderr: String,
+}
+
+impl ProcessOutput {
+    pub fn success(&self) -> bool {
+        self.status.is_some_and(|status| status.success())
+    }
+
+    pub fn timed_out(&self) -> bool {
+        self.status.is_none()
+    }
+}
+
+/// Where the output of a running process goes besides the kept tail
+#[derive(Clone, Default)]
+pub struct OutputSink {
+    /// Print lines live with this prefix. Nothing is printed if it's `None`
+    pub prefix: Option<String>,
+    /// Full log of both streams
+    pub log: Option<Arc<Mutex<std::fs::File>>>,
+}
+
+#[derive(Clone, Copy)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Run the command streaming its output line by line to the sink.
+/// Pipes are drained in separate threads so a chatty process
+/// never blocks on a full pipe while we wait for it
+pub fn run(
+    command: &mut Command,
+    timeout: Option<Duration>,
+    sink: &OutputSink,
+) -> std::io::Result<ProcessOutput> {
+    // A hook is usually a shell running other processes,
+    // so the whole group has to be killed on timeout. A separate group
+    // is not used otherwise: it could not read from the terminal
+    #[cfg(unix)]
+    if timeout.is_some() {
+        std::os::unix::process::CommandExt::process_group(command, 0);
+    }
+
+    let mut child = command
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    let stdout_reader = drain(child.stdout.take(), Stream::Stdout, sink.clone());
+    let stderr_reader = drain(child.stderr.take(), Stream::Stderr, sink.clone());
+    let status = wait(&mut child, timeout)?;
+
+    Ok(ProcessOutput {
+        status,
+        stdout: stdout_reader.join().unwrap_or_default(),
+        stderr: stderr_reader.join().unwrap_or_default(),
+    })
+}
+
+fn drain<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    stream: Stream,
+    sink: OutputSink,
+) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut tail = VecDeque::with_capacity(TAIL_LINES);
+        let mut reader = match pipe {
+            Some(pipe) => BufReader::new(pipe),
+            None => return String::new(),
+        };
+        let mut raw_line = vec![];
+        while let Ok(read) = reader.read_until(b'\n', &mut raw_line) {
+            if read == 0 {
+                break;
+            }
+            let line = String::from_utf8_lossy(&raw_line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if let Some(prefix) = &sink.prefix {
+                match stream {
+                    Stream::Stdout => println!("{} {}", prefix.cyan(), line.bright_black()),
+                    Stream::Stderr => eprintln!("{} {}", prefix.yellow(), line),
+                }
+            }
+            if let Some(log) = &sink.log {
+                if let Ok(mut log) = log.lock() {
+                    log.write_all(&raw_line).unwrap_or_default();
+                    if !raw_line.ends_with(b"\n") {
+                        log.write_all(b"\n").unwrap_or_default();
+                    }
+                }
+            }
+
+            if tail.len() == TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(format!("{}\n", line));
+            raw_line.clear();
+        }
+        tail.into_iter().collect()
+    })
+}
+
+fn wait(child: &mut Child, timeout: Option<Duration>) -> std::io::Result<Option<ExitStatus>> {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return child.wait().map(Some),
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            kill(child)?;
+            child.wait()?;
+            return Ok(None);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(unix)]
+fn kill(child: &mut Child) -> std::io::Result<()> {
+    // Negative pid means the process group created in `run`
+    if unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) } == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(unix))]
+fn kill(child: &mut Child) -> std::io::Result<()> {
+    child.kill()
+}
@@ -0,0 +1,156 @@
+use serde_derive::{Deserialize, Serialize};
+
+use crate::context::{ChangedVersion, HookStage, ProfileContext, Version};
+
+/// How commands report what they have done
+#[derive(Debug, Clone, PartialEq, clap::ArgEnum)]
+pub enum OutputFormat {
+    /// Human-friendly colored messages
+    Text,
+    /// One JSON document printed to stdout at the end of a run
+    Json,
+    /// Nothing is printed, used by the library
+    #[clap(skip)]
+    Silent,
+}
+
+/// Everything a `bump` or `move` run has done
+#[derive(Serialize, Debug)]
+pub struct Report {
+    pub profile: String,
+    pub bumped_part: Option<String>,
+    pub read_only: bool,
+    pub old: VersionReport,
+    pub new: VersionReport,
+    pub files: Vec<FileReport>,
+    pub hooks: Vec<HookReport>,
+    pub git: Option<GitReport>,
+    pub changelog: Option<ChangelogReport>,
+    /// Why the part has been chosen for `bump <profile> auto`
+    pub auto: Option<AutoReport>,
+    /// Profiles changed because they depend on this one (transitively)
+    pub dependents: Vec<Report>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VersionReport {
+    pub version: String,
+    pub parts: Version,
+}
+
+/// One applied file view
+#[derive(Serialize, Debug)]
+pub struct FileReport {
+    pub path: String,
+    pub old_version: String,
+    pub new_version: String,
+    /// 1-based numbers of lines where the old version has been replaced
+    pub lines: Vec<usize>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ChangelogReport {
+    pub path: String,
+    /// Heading the "Unreleased" one has been renamed to
+    pub heading: String,
+    /// How many entries have been generated from commits
+    pub entries: usize,
+    #[serde(skip)]
+    pub unreleased: String,
+    /// Text inserted right after the "Unreleased" heading
+    #[serde(skip)]
+    pub inserted: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct GitReport {
+    /// Hash of the created commit
+    pub commit: Option<String>,
+    pub tag: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AutoReport {
+    /// Tag commits are collected since (all the history if there is no tag)
+    pub since_tag: Option<String>,
+    pub commits: Vec<CommitReport>,
+    pub part: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CommitReport {
+    pub hash: String,
+    pub subject: String,
+    /// `None` if the commit does not bump anything
+    pub part: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct HookReport {
+    pub name: String,
+    pub stage: HookStage,
+    pub command: Vec<String>,
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub timed_out: bool,
+    pub attempts: u32,
+    /// Last lines of the hook's stdout
+    pub stdout: String,
+    /// Last lines of the hook's stderr
+    pub stderr: String,
+    /// Path to the full log if saving logs is enabled
+    pub log: Option<String>,
+}
+
+/// Problems `weee validate` has found in a profile
+#[derive(Serialize, Debug, Clone)]
+pub struct ValidationReport {
+    pub profile: String,
+    pub problems: Vec<Problem>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Problem {
+    /// `syntax`, `config`, `spelling`, `files`, `liquid` or `hooks`
+    pub check: &'static str,
+    pub message: String,
+    /// 1-based position in the profile if it's known
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// File the position is in if it's not the profile's own one
+    /// (or the profile is merged from several files)
+    pub file: Option<String>,
+}
+
+impl Report {
+    pub fn new(
+        profile_ctx: &ProfileContext,
+        bumped_part: Option<&str>,
+        changed_version: &ChangedVersion,
+        read_only: bool,
+    ) -> Self {
+        Report {
+            profile: profile_ctx.qualified_name(),
+            bumped_part: bumped_part.map(String::from),
+            read_only,
+            old: VersionReport {
+                version: profile_ctx.version_to_string(&changed_version.old),
+                parts: changed_version.old.clone(),
+            },
+            new: VersionReport {
+                version: profile_ctx.version_to_string(&changed_version.new),
+                parts: changed_version.new.clone(),
+            },
+            files: vec![],
+            hooks: vec![],
+            git: None,
+            changelog: None,
+            auto: None,
+            dependents: vec![],
+        }
+    }
+
+    pub fn print_json(&self) {
+        println!("{}", serde_json::to_string_pretty(self).unwrap());
+    }
+}
@@ -0,0 +1,380 @@
+//! JSON Schema of `.weee/*.version.toml` for editors (i.e. taplo).
+//! Keep it in sync with `config`: serde representations there are the source of truth
+
+use serde_json::{json, Value};
+
+const DRAFT: &str = "http://json-schema.org/draft-07/schema#";
+
+/// Schema of a profile file with every config type in `definitions`
+pub fn profile_schema() -> Value {
+    json!({
+        "$schema": DRAFT,
+        "title": "weee profile",
+        "description": "Version profile of weee stored in `.weee/<profile>.version.toml`",
+        "$ref": "#/definitions/ProfileConfig",
+        "definitions": {
+            "ProfileConfig": profile_config(),
+            "Part": part(),
+            "Factory": factory(),
+            "IncrementPayload": increment_payload(),
+            "IntegerOrString": integer_or_string(),
+            "File": file(),
+            "FileVersion": file_version(),
+            "Hooks": hooks(),
+            "Hook": hook(),
+            "DetailedHook": detailed_hook(),
+            "Git": git(),
+            "ConventionalCommits": conventional_commits(),
+            "Changelog": changelog(),
+            "Dependency": dependency(),
+        }
+    })
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/definitions/{}", name) })
+}
+
+fn files(description: &str) -> Value {
+    json!({
+        "type": "object",
+        "description": description,
+        "additionalProperties": {
+            "type": "array",
+            "items": reference("File")
+        }
+    })
+}
+
+fn string_map(description: &str) -> Value {
+    json!({
+        "type": "object",
+        "description": description,
+        "additionalProperties": { "type": "string" }
+    })
+}
+
+fn profile_config() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "extends": {
+                "type": "string",
+                "description": "Profile whose parts, files and hooks are merged into this one, i.e. `base` for `.weee/base.version.toml`"
+            },
+            "include": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Fragments relative to `.weee/` merged after the extended profile in the listed order"
+            },
+            "parts": {
+                "type": "object",
+                "description": "Version parts in the order they are written, i.e. `major`, `minor`, `patch`",
+                "additionalProperties": reference("Part")
+            },
+            "files": files("Files relative to the project's root (`/`-separated) with the ways the version is written in them"),
+            "hooks": reference("Hooks"),
+            "git": reference("Git"),
+            "conventional_commits": reference("ConventionalCommits"),
+            "changelog": reference("Changelog"),
+            "dependencies": {
+                "type": "object",
+                "description": "Profiles whose versions this one refers to, by name (`<member>:<profile>` for workspace members)",
+                "additionalProperties": reference("Dependency")
+            },
+        },
+        "anyOf": [
+            { "required": ["parts", "files"] },
+            { "required": ["extends"] },
+            { "required": ["include"] }
+        ],
+        "additionalProperties": false
+    })
+}
+
+fn part() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "value": {
+                "description": "Current value of the part",
+                "$ref": "#/definitions/IntegerOrString"
+            },
+            "factory": {
+                "description": "Required unless the part is defined in the extended profile or an included file",
+                "$ref": "#/definitions/Factory"
+            },
+        },
+        "required": ["value"],
+        "additionalProperties": false
+    })
+}
+
+fn factory() -> Value {
+    json!({
+        "description": "How the next value of the part is produced. `name` selects the factory, `payload` configures it",
+        "oneOf": [
+            {
+                "type": "object",
+                "description": "Integer incremented by one. It's reset to `payload.default` when a more significant part is bumped",
+                "properties": {
+                    "name": { "const": "increment" },
+                    "payload": reference("IncrementPayload"),
+                },
+                "required": ["name"],
+                "additionalProperties": false
+            },
+            {
+                "type": "object",
+                "description": "Cycles through the values of `payload`, i.e. `[\"alpha\", \"beta\", \"rc\"]`. The part's value must be one of them",
+                "properties": {
+                    "name": { "const": "loop" },
+                    "payload": {
+                        "type": "array",
+                        "items": reference("IntegerOrString"),
+                        "minItems": 1
+                    },
+                },
+                "required": ["name", "payload"],
+                "additionalProperties": false
+            }
+        ]
+    })
+}
+
+fn increment_payload() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "default": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "Value the part is reset to. Defaults to 0"
+            }
+        },
+        "additionalProperties": false
+    })
+}
+
+fn integer_or_string() -> Value {
+    json!({
+        "description": "Non-negative integer or a string",
+        "oneOf": [
+            { "type": "integer", "minimum": 0 },
+            { "type": "string" }
+        ]
+    })
+}
+
+fn file() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "version": reference("FileVersion"),
+            "replaces_count": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "How many entries of the old version are replaced. All of them by default"
+            },
+            "enable_liquid_tempaltes": {
+                "type": "boolean",
+                "description": "Treat `version.view` as a liquid template (the option's name is spelled exactly like this)"
+            },
+        },
+        "required": ["version"],
+        "additionalProperties": false
+    })
+}
+
+fn file_version() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "view": {
+                "type": "string",
+                "description": "How the version is written, i.e. `{major}.{minor}.{patch}`"
+            },
+            "placement": {
+                "type": "string",
+                "description": "Text around the version in the file, i.e. `version = \"{version}\"`"
+            },
+        },
+        "required": ["view", "placement"],
+        "additionalProperties": false
+    })
+}
+
+fn hooks() -> Value {
+    let stage = |description: &str| {
+        json!({
+            "type": "object",
+            "description": description,
+            "additionalProperties": reference("Hook")
+        })
+    };
+    json!({
+        "type": "object",
+        "properties": {
+            "before": stage("Executed when the new version is known but no files are changed yet. A failed hook aborts the bump"),
+            "afterwords": stage("Executed after files are changed"),
+            "on_failure": stage("Executed when replacing versions in files or an afterword hook failed"),
+            "save_logs": {
+                "type": "boolean",
+                "description": "Save full output of every hook into `.weee/logs/`"
+            },
+        },
+        "additionalProperties": false
+    })
+}
+
+fn hook() -> Value {
+    json!({
+        "description": "A plain command with arguments or a table with the command and conditions when it should be executed. Arguments may use `!ASK:`, `!EDITOR:`, `!ENV:`, `!FILE:` and `!FORMAT:` prefixes",
+        "oneOf": [
+            {
+                "type": "array",
+                "items": { "type": "string" },
+                "minItems": 1
+            },
+            reference("DetailedHook")
+        ]
+    })
+}
+
+fn detailed_hook() -> Value {
+    let strings = |description: &str| {
+        json!({
+            "type": "array",
+            "items": { "type": "string" },
+            "description": description
+        })
+    };
+    json!({
+        "type": "object",
+        "properties": {
+            "args": strings("Command with arguments. Conflicts with `shell`"),
+            "shell": {
+                "type": "string",
+                "description": "Command line executed with `sh -c` (`cmd /C` on Windows)"
+            },
+            "cwd": {
+                "type": "string",
+                "description": "Working directory relative to the project's root"
+            },
+            "timeout_secs": { "type": "integer", "minimum": 0 },
+            "env": string_map("Environment variables of the hook"),
+            "allow_failure": {
+                "type": "boolean",
+                "description": "Do not abort the bump if the hook failed"
+            },
+            "retries": {
+                "type": "integer",
+                "minimum": 0,
+                "description": "How many times the hook is re-executed if it failed"
+            },
+            "only_parts": strings("Execute only if one of these parts is bumped"),
+            "only_profiles": strings("Execute only for these profiles"),
+            "when": {
+                "type": "string",
+                "description": "Liquid predicate like `{{ new.stage == 'final' }}`"
+            },
+            "skip_in_read_only": { "type": "boolean" },
+        },
+        "oneOf": [
+            { "required": ["args"] },
+            { "required": ["shell"] }
+        ],
+        "additionalProperties": false
+    })
+}
+
+fn git() -> Value {
+    json!({
+        "type": "object",
+        "description": "Built-in git integration",
+        "properties": {
+            "require_clean": {
+                "type": "boolean",
+                "description": "Refuse to bump if there are changes in files not managed by the profile"
+            },
+            "commit": {
+                "type": "boolean",
+                "description": "Commit files changed by weee (and only them)"
+            },
+            "commit_message": {
+                "type": "string",
+                "description": "Liquid template of the commit message"
+            },
+            "tag": {
+                "type": "string",
+                "description": "Liquid template of a tag name. No tag is created if it's not set. Requires `commit`, so the tag is put on the commit with the new version"
+            },
+            "tag_message": {
+                "type": "string",
+                "description": "Liquid template of the tag message. The tag is annotated if it's set"
+            },
+        },
+        "additionalProperties": false
+    })
+}
+
+fn conventional_commits() -> Value {
+    json!({
+        "type": "object",
+        "description": "How `bump <profile> auto` picks a part from Conventional Commits made since the last version tag",
+        "properties": {
+            "types": string_map("Commit type to part, i.e. `feat = \"minor\"`. Defaults to `feat` → `minor` and `fix` → `patch`"),
+            "breaking": {
+                "type": "string",
+                "description": "Part bumped for breaking changes. Defaults to `major`"
+            },
+            "tag_pattern": {
+                "type": "string",
+                "description": "Glob pattern of version tags. Defaults to the `git.tag` template with liquid replaced by `*` (i.e. `v{{ new_version }}` gives `v*`), or `v*` if there is no `git.tag`"
+            },
+        },
+        "additionalProperties": false
+    })
+}
+
+fn changelog() -> Value {
+    json!({
+        "type": "object",
+        "description": "Promotes the \"Unreleased\" section of a changelog on every bump",
+        "properties": {
+            "path": {
+                "type": "string",
+                "description": "Path relative to the project's root. Defaults to `CHANGELOG.md`"
+            },
+            "unreleased": {
+                "type": "string",
+                "description": "Heading of the section with unreleased changes. Defaults to `## [Unreleased]`"
+            },
+            "heading": {
+                "type": "string",
+                "description": "Liquid template of the released section's heading"
+            },
+            "from_commits": {
+                "type": "boolean",
+                "description": "Fill the released section from Conventional Commits since the last version tag"
+            },
+            "sections": string_map("Commit type to section title, i.e. `feat = \"Added\"`"),
+        },
+        "additionalProperties": false
+    })
+}
+
+fn dependency() -> Value {
+    json!({
+        "type": "object",
+        "description": "References to another profile's version, rewritten whenever the dependency is bumped",
+        "properties": {
+            "bump": {
+                "type": "string",
+                "description": "Part of this profile bumped together with the dependency"
+            },
+            "files": files("Files relative to this profile's root with the ways the dependency's version is written in them. Views use the dependency's parts"),
+        },
+        "additionalProperties": false
+    })
+}
@@ -0,0 +1,143 @@
+#![cfg(test)]
+#![cfg(unix)]
+
+use clap::Parser;
+use serial_test::serial;
+
+use crate::commands::CLI;
+use crate::tests::utils::simple_project::SimpleProject;
+
+const CHANGELOG: &str = "# Changelog
+
+## [Unreleased]
+
+- Manually written entry
+
+## [0.1.0] - 2022-01-01
+
+- First release
+";
+
+#[test]
+#[serial]
+fn unreleased_section_is_promoted() {
+    let project = SimpleProject::setup();
+    std::fs::write(project.path.join("CHANGELOG.md"), CHANGELOG).unwrap();
+    project.extend_profile("dep", "\n[changelog]\n");
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "minor"]).handle(),
+        0
+    );
+    let now = liquid::model::DateTime::now();
+    let today = format!("{:04}-{:02}-{:02}", now.year(), now.month(), now.day());
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("CHANGELOG.md")).unwrap(),
+        format!(
+            "# Changelog
+
+## [Unreleased]
+
+## [0.2.0-alpha0] - {}
+
+- Manually written entry
+
+## [0.1.0] - 2022-01-01
+
+- First release
+",
+            today
+        )
+    );
+    assert_eq!(project.fetch_versions().dep, "0.2.0-alpha0");
+}
+
+#[test]
+#[serial]
+fn entries_from_commits() {
+    let project = SimpleProject::setup();
+    std::fs::write(project.path.join("CHANGELOG.md"), CHANGELOG).unwrap();
+    project.extend_profile(
+        "dep",
+        r###"
+[changelog]
+heading = "## {{ new.major }}.{{ new.minor }}"
+from_commits = true
+sections = { feat = "Features", fix = "Bug fixes", perf = "Bug fixes", build-deps = "Dependencies" }
+
+[git]
+commit = true
+tag = "v{{ new.major }}.{{ new.minor }}"
+"###,
+    );
+    project.init_git();
+    project.git(&["tag", "v0.1"]);
+    for message in [
+        "fix(parser): handle empty input",
+        "docs: typo",
+        "feat!: new config format",
+        "perf: faster startup",
+        "build-deps: update liquid",
+        "not a conventional commit",
+    ] {
+        project.git(&["commit", "--quiet", "--allow-empty", "-m", message]);
+    }
+
+    let output = project.run_binary(&["bump", "dep", "major", "--output", "json"]);
+    assert_eq!(output.status.code(), Some(0));
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["changelog"]["heading"], "## 1.0");
+    assert_eq!(document["changelog"]["entries"], 4);
+
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("CHANGELOG.md")).unwrap(),
+        "# Changelog
+
+## [Unreleased]
+
+## 1.0
+
+### Features
+
+- **BREAKING** new config format
+
+### Bug fixes
+
+- **parser:** handle empty input
+- faster startup
+
+### Dependencies
+
+- update liquid
+
+- Manually written entry
+
+## [0.1.0] - 2022-01-01
+
+- First release
+"
+    );
+    // The changelog is committed together with the other files
+    assert_eq!(
+        project.git(&["status", "--porcelain", "--", ".", ":!.weee/history.jsonl"]),
+        ""
+    );
+}
+
+#[test]
+#[serial]
+fn missing_unreleased_section_aborts_bump() {
+    let project = SimpleProject::setup();
+    std::fs::write(
+        project.path.join("CHANGELOG.md"),
+        CHANGELOG.replace("## [Unreleased]", "## Unreleased"),
+    )
+    .unwrap();
+    project.extend_profile("dep", "\n[changelog]\n");
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "minor"]).handle(),
+        37
+    );
+    assert_eq!(project.fetch_versions().dep, "0.1.0-alpha0");
+}
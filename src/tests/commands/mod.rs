@@ -1,2 +1,4 @@
 mod bump;
 mod r#move;
+mod next;
+mod show;
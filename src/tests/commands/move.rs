@@ -31,7 +31,7 @@ mod simple_project {
                 .spawn()
                 .unwrap();
 
-            let child_stdin = child.stdin.as_mut().unwrap();
+            let mut child_stdin = child.stdin.take().unwrap();
             child_stdin
                 .write_all(b"40\n3\n8\nalpha\na\n-1\n14\n")
                 .unwrap();
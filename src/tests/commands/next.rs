@@ -0,0 +1,55 @@
+#![cfg(test)]
+
+use serial_test::serial;
+
+use crate::tests::utils::simple_project::SimpleProject;
+
+#[cfg(test)]
+mod simple_project {
+
+    use super::*;
+
+    #[test]
+    #[serial]
+    fn next_rendered() {
+        let project = SimpleProject::setup();
+        let output = project.run_binary(&["next", "project", "stage", "--file", "pyproject.toml"]);
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "14.23.5646a0\n");
+        assert_eq!(
+            SimpleProject::untouched_versions(),
+            project.fetch_versions()
+        );
+
+        let output = project.run_binary(&["next", "dep", "minor"]);
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "0.2.0.alpha.0\n");
+        assert_eq!(
+            SimpleProject::untouched_versions(),
+            project.fetch_versions()
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn next_json() {
+        let project = SimpleProject::setup();
+        let output = project.run_binary(&["next", "dep", "major", "--json"]);
+        let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(document["version"], "1.0.0.alpha.0");
+        assert_eq!(document["bumped_part"], "major");
+        assert_eq!(document["parts"]["major"], 1);
+        assert_eq!(document["old"]["version"], "0.1.0.alpha.0");
+        assert_eq!(
+            SimpleProject::untouched_versions(),
+            project.fetch_versions()
+        );
+
+        let output = project.run_binary(&["--output", "json", "next", "dep", "minor"]);
+        let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(document["version"], "0.2.0.alpha.0");
+        assert_eq!(document["old"]["version"], "0.1.0.alpha.0");
+
+        // The profile's storage should be kept too
+        let output = project.run_binary(&["show", "dep"]);
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "0.1.0.alpha.0\n");
+    }
+}
@@ -0,0 +1,86 @@
+#![cfg(test)]
+
+use serial_test::serial;
+
+use crate::tests::utils::simple_project::SimpleProject;
+
+fn run_show(args: &[&str]) -> String {
+    let project = SimpleProject::setup();
+    let output = project.run_binary(&[&["show"], args].concat());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[cfg(test)]
+mod simple_project {
+
+    use super::*;
+
+    #[test]
+    #[serial]
+    fn show_default() {
+        assert_eq!(run_show(&["dep"]), "0.1.0.alpha.0\n");
+    }
+
+    #[test]
+    #[serial]
+    fn show_file_view() {
+        assert_eq!(
+            run_show(&["project", "--file", "pyproject.toml"]),
+            "14.23.5645b3\n"
+        );
+        assert_eq!(
+            run_show(&["dep", "--file", "req.txt", "--nth", "1"]),
+            "0.1\n"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn show_custom_format() {
+        assert_eq!(
+            run_show(&["dep", "--format", "{major}-{stage}"]),
+            "0-alpha\n"
+        );
+        assert_eq!(
+            run_show(&["dep", "--format", "{{ stage | upcase }}", "--liquid"]),
+            "ALPHA\n"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn show_json() {
+        let document: serde_json::Value =
+            serde_json::from_str(&run_show(&["project", "--json"])).unwrap();
+        assert_eq!(
+            document,
+            serde_json::json!({
+                "profile": "project",
+                "version": "14.23.5645.b.3",
+                "parts": {
+                    "major": 14,
+                    "minor": 23,
+                    "patch": 5645,
+                    "stage": "b",
+                    "step": 3
+                }
+            })
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn show_global_json_output() {
+        let document: serde_json::Value =
+            serde_json::from_str(&run_show(&["dep", "--output", "json"])).unwrap();
+        assert_eq!(document["version"], "0.1.0.alpha.0");
+
+        let document: serde_json::Value = serde_json::from_str(&run_show(&[
+            "dep", "--file", "req.txt", "--nth", "1", "--output", "json",
+        ]))
+        .unwrap();
+        assert_eq!(document["version"], "0.1");
+        assert_eq!(document["view"], "0.1");
+        assert_eq!(document["parts"]["minor"], 1);
+    }
+}
@@ -0,0 +1,172 @@
+#![cfg(test)]
+
+use clap::Parser;
+use serial_test::serial;
+
+use crate::commands::CLI;
+use crate::config::IntegerOrString;
+use crate::error::Error;
+use crate::tests::utils::simple_project::SimpleProject;
+use crate::validation;
+use crate::{BumpOperation, ProfileContext, RTContext};
+
+/// Move parts of `dep` into `base` and its hooks into an included fragment
+fn split_dep_profile(project: &SimpleProject) {
+    let weee_path = project.path.join(".weee");
+    let content = std::fs::read_to_string(weee_path.join("dep.version.toml")).unwrap();
+    let (parts, files) = content.split_at(content.find("[[files.").unwrap());
+    std::fs::write(weee_path.join("base.version.toml"), parts).unwrap();
+    std::fs::write(
+        weee_path.join("common-hooks.toml"),
+        r#"
+[hooks.afterwords]
+notify = ["echo", "bumped"]
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        weee_path.join("dep.version.toml"),
+        format!(
+            r#"extends = "base"
+include = ["common-hooks.toml"]
+
+[parts.patch]
+value = 7
+
+[hooks.afterwords]
+list = ["ls"]
+
+{}"#,
+            files
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+#[serial]
+fn sources_are_merged() {
+    let project = SimpleProject::setup();
+    split_dep_profile(&project);
+    let ctx = RTContext::at(&project.path);
+
+    let profile_ctx = ProfileContext::load(&ctx, "dep").unwrap();
+    let config = &profile_ctx.profile_model;
+    let parts: Vec<_> = config.parts.keys().map(String::as_str).collect();
+    assert_eq!(parts, vec!["major", "minor", "patch", "stage", "step"]);
+    assert_eq!(config.parts["patch"].value, IntegerOrString::Integer(7));
+    assert_eq!(config.parts["minor"].value, IntegerOrString::Integer(1));
+    assert_eq!(config.files["req.txt"].len(), 2);
+
+    let afterwords = config.hooks.as_ref().unwrap().afterwords.as_ref().unwrap();
+    let hooks: Vec<_> = afterwords.keys().map(String::as_str).collect();
+    assert_eq!(hooks, vec!["notify", "list"]);
+}
+
+#[test]
+#[serial]
+fn values_are_written_to_own_file() {
+    let project = SimpleProject::setup();
+    split_dep_profile(&project);
+    std::fs::write(
+        project.path.join("req.txt"),
+        "dep==0.1.7-alpha0\ndep-another-style==0.1\n",
+    )
+    .unwrap();
+    let base = std::fs::read_to_string(project.path.join(".weee/base.version.toml")).unwrap();
+
+    let report = BumpOperation::new("dep", "minor")
+        .run(&RTContext::at(&project.path))
+        .unwrap();
+    assert_eq!(report.new.version, "0.2.0.alpha.0");
+    assert_eq!(project.fetch_versions().dep, "0.2.0-alpha0");
+
+    let own = std::fs::read_to_string(project.path.join(".weee/dep.version.toml")).unwrap();
+    assert!(own.starts_with("extends = \"base\""));
+    let own: toml::Value = toml::from_str(&own).unwrap();
+    assert_eq!(own["parts"]["minor"]["value"].as_integer(), Some(2));
+    assert_eq!(own["parts"]["patch"]["value"].as_integer(), Some(0));
+    assert!(own["parts"]["minor"].get("factory").is_none());
+    assert_eq!(
+        std::fs::read_to_string(project.path.join(".weee/base.version.toml")).unwrap(),
+        base
+    );
+}
+
+#[test]
+#[serial]
+fn inheritance_cycle_is_reported() {
+    let project = SimpleProject::setup();
+    split_dep_profile(&project);
+    let base_path = project.path.join(".weee/base.version.toml");
+    let base = std::fs::read_to_string(&base_path).unwrap();
+    std::fs::write(&base_path, format!("extends = \"dep\"\n{}", base)).unwrap();
+
+    let error = match ProfileContext::load(&RTContext::at(&project.path), "dep") {
+        Ok(_) => panic!("Cycle is not detected"),
+        Err(error) => error,
+    };
+    assert_eq!(error.title(), "ProfileInheritanceCycle");
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "patch"]).handle(),
+        90
+    );
+}
+
+#[test]
+#[serial]
+fn problems_point_to_their_sources() {
+    let project = SimpleProject::setup();
+    split_dep_profile(&project);
+    let weee_path = project.path.join(".weee");
+    let own = std::fs::read_to_string(weee_path.join("dep.version.toml")).unwrap();
+    std::fs::write(
+        weee_path.join("dep.version.toml"),
+        own.replacen(
+            "[parts.patch]\nvalue = 7\n",
+            "[parts.patch]\nvalue = 7\n\n[parts.stage]\nvalue = \"gamma\"\n",
+            1,
+        ),
+    )
+    .unwrap();
+
+    let ctx = RTContext::at(&project.path);
+    let diagnostics = match ProfileContext::load(&ctx, "dep") {
+        Ok(_) => panic!("Profile should be invalid"),
+        Err(Error::InvalidProfileConfig { diagnostics, .. }) => diagnostics,
+        Err(other) => panic!("Unexpected error: {}", other),
+    };
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].path,
+        Some(project.path.join(".weee/dep.version.toml"))
+    );
+    assert_eq!((diagnostics[0].line, diagnostics[0].column), (8, 9));
+    assert_eq!(diagnostics[0].source_line, "value = \"gamma\"");
+
+    // Type errors are found in the file they are made in
+    let base = std::fs::read_to_string(weee_path.join("base.version.toml")).unwrap();
+    std::fs::write(
+        weee_path.join("base.version.toml"),
+        base.replacen("factory.name = \"loop\"", "factory.name = \"lop\"", 1),
+    )
+    .unwrap();
+    let problems = validation::validate_profile(&ctx, "dep").unwrap();
+    assert_eq!(problems.len(), 1);
+    assert_eq!(
+        problems[0].file,
+        Some(
+            project
+                .path
+                .join(".weee/base.version.toml")
+                .display()
+                .to_string()
+        )
+    );
+    assert_eq!(
+        problems[0].line,
+        base.lines()
+            .position(|line| line.contains("\"loop\""))
+            .map(|index| index + 1)
+    );
+}
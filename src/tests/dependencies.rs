@@ -0,0 +1,300 @@
+#![cfg(test)]
+#![cfg(unix)]
+
+use clap::Parser;
+use serial_test::serial;
+
+use crate::commands::CLI;
+use crate::config::IntegerOrString;
+use crate::tests::utils::simple_project::SimpleProject;
+use crate::{BumpOperation, MoveOperation, MultiBumpOperation, RTContext};
+
+/// `project` refers to `dep` in `pyproject.toml`
+fn depend_on_dep(project: &SimpleProject, bump: Option<&str>) {
+    let bump = match bump {
+        Some(part) => format!("bump = \"{}\"\n", part),
+        None => String::new(),
+    };
+    project.extend_profile(
+        "project",
+        &format!(
+            r#"
+[dependencies.dep]
+{}
+[[dependencies.dep.files."pyproject.toml"]]
+version.view = "{{major}}.{{minor}}"
+version.placement = "dep = \"^{{version}}\""
+"#,
+            bump
+        ),
+    );
+    std::fs::write(
+        project.path.join("pyproject.toml"),
+        "project.version = \"14.23.5645b3\"\ndep = \"^0.1\"\n",
+    )
+    .unwrap();
+}
+
+/// `app` refers to both `project` and `dep` in `app.txt`
+fn add_app_profile(project: &SimpleProject) {
+    std::fs::write(
+        project.path.join(".weee/app.version.toml"),
+        r#"[parts.major]
+value = 2
+factory.name = "increment"
+
+[parts.patch]
+value = 0
+factory.name = "increment"
+
+[[files."app.txt"]]
+version.view = "{major}.{patch}"
+version.placement = "app {version}"
+
+[dependencies.project]
+bump = "patch"
+
+[[dependencies.project.files."app.txt"]]
+version.view = "{major}.{minor}.{patch}"
+version.placement = "project {version}"
+
+[dependencies.dep]
+
+[[dependencies.dep.files."app.txt"]]
+version.view = "{major}.{minor}.{patch}"
+version.placement = "dep {version}"
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        project.path.join("app.txt"),
+        "app 2.0\nproject 14.23.5645\ndep 0.1.0\n",
+    )
+    .unwrap();
+}
+
+#[test]
+#[serial]
+fn dependent_is_bumped_with_its_references() {
+    let project = SimpleProject::setup();
+    depend_on_dep(&project, Some("patch"));
+
+    let report = BumpOperation::new("dep", "minor")
+        .run(&RTContext::at(&project.path))
+        .unwrap();
+    assert_eq!(report.new.version, "0.2.0.alpha.0");
+    assert_eq!(report.dependents.len(), 1);
+    let dependent = &report.dependents[0];
+    assert_eq!(dependent.profile, "project");
+    assert_eq!(dependent.bumped_part.as_deref(), Some("patch"));
+    assert_eq!(dependent.new.version, "14.23.5646.a.0");
+
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("pyproject.toml")).unwrap(),
+        "project.version = \"14.23.5646a0\"\ndep = \"^0.2\"\n"
+    );
+    assert_eq!(project.fetch_versions().dep, "0.2.0-alpha0");
+
+    assert_eq!(CLI::parse_from(["weee", "undo"]).handle(), 0);
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("pyproject.toml")).unwrap(),
+        "project.version = \"14.23.5645b3\"\ndep = \"^0.1\"\n"
+    );
+    assert_eq!(project.fetch_versions().dep, "0.1.0-alpha0");
+}
+
+#[test]
+#[serial]
+fn only_references_are_rewritten_without_bump() {
+    let project = SimpleProject::setup();
+    depend_on_dep(&project, None);
+    let profile_path = project.path.join(".weee/project.version.toml");
+    let profile = std::fs::read_to_string(&profile_path).unwrap();
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        0
+    );
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("pyproject.toml")).unwrap(),
+        "project.version = \"14.23.5645b3\"\ndep = \"^1.0\"\n"
+    );
+    assert_eq!(std::fs::read_to_string(&profile_path).unwrap(), profile);
+
+    // References are restored together with the dependency
+    assert_eq!(CLI::parse_from(["weee", "undo"]).handle(), 0);
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("pyproject.toml")).unwrap(),
+        "project.version = \"14.23.5645b3\"\ndep = \"^0.1\"\n"
+    );
+    assert_eq!(project.fetch_versions().dep, "0.1.0-alpha0");
+    assert_eq!(std::fs::read_to_string(&profile_path).unwrap(), profile);
+}
+
+#[test]
+#[serial]
+fn moves_are_propagated_too() {
+    let project = SimpleProject::setup();
+    depend_on_dep(&project, Some("patch"));
+
+    let report = MoveOperation::new("dep")
+        .part("minor", IntegerOrString::Integer(5))
+        .run(&RTContext::at(&project.path))
+        .unwrap();
+    assert_eq!(report.dependents.len(), 1);
+    assert_eq!(report.dependents[0].new.version, "14.23.5646.a.0");
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("pyproject.toml")).unwrap(),
+        "project.version = \"14.23.5646a0\"\ndep = \"^0.5\"\n"
+    );
+}
+
+#[test]
+#[serial]
+fn bumps_go_through_dependents_in_order() {
+    let project = SimpleProject::setup();
+    depend_on_dep(&project, Some("patch"));
+    add_app_profile(&project);
+
+    let report = BumpOperation::new("dep", "major")
+        .run(&RTContext::at(&project.path))
+        .unwrap();
+    let dependents: Vec<_> = report
+        .dependents
+        .iter()
+        .map(|dependent| (dependent.profile.as_str(), dependent.new.version.as_str()))
+        .collect();
+    assert_eq!(
+        dependents,
+        vec![("project", "14.23.5646.a.0"), ("app", "2.1")]
+    );
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("app.txt")).unwrap(),
+        "app 2.1\nproject 14.23.5646\ndep 1.0.0\n"
+    );
+
+    // A dependent bumped explicitly keeps its part
+    let reports = MultiBumpOperation::new(["app", "project"], "major")
+        .run(&RTContext::at(&project.path))
+        .unwrap();
+    let bumped: Vec<_> = reports
+        .iter()
+        .map(|report| (report.profile.as_str(), report.new.version.as_str()))
+        .collect();
+    assert_eq!(bumped, vec![("app", "3.0"), ("project", "15.0.0.a.0")]);
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("app.txt")).unwrap(),
+        "app 3.0\nproject 15.0.0\ndep 1.0.0\n"
+    );
+}
+
+#[test]
+#[serial]
+fn dependent_is_committed_with_its_dependency() {
+    let project = SimpleProject::setup();
+    depend_on_dep(&project, None);
+    project.extend_profile(
+        "dep",
+        r#"
+[git]
+commit = true
+tag = "dep-{{ new_version }}"
+"#,
+    );
+    project.extend_profile(
+        "project",
+        r#"
+[git]
+commit = true
+tag = "v{{ new_version }}"
+"#,
+    );
+    project.init_git();
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "minor"]).handle(),
+        0
+    );
+    assert_eq!(
+        project.git(&["show", "--name-only", "--format=%s", "HEAD"]),
+        "Bump version: 0.1.0-alpha0 → 0.2.0-alpha0\n\n.weee/dep.version.toml\npyproject.toml\nreq.txt"
+    );
+    assert_eq!(
+        project.git(&["tag", "--points-at", "HEAD"]),
+        "dep-0.2.0-alpha0"
+    );
+}
+
+#[test]
+#[serial]
+fn unreadable_profiles_do_not_stop_bumps() {
+    let project = SimpleProject::setup();
+    depend_on_dep(&project, Some("patch"));
+    std::fs::write(project.path.join(".weee/wip.version.toml"), "[parts\n").unwrap();
+
+    let report = BumpOperation::new("dep", "minor")
+        .run(&RTContext::at(&project.path))
+        .unwrap();
+    assert_eq!(report.dependents[0].profile, "project");
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "wip", "minor"]).handle(),
+        15
+    );
+}
+
+#[test]
+#[serial]
+fn broken_dependencies_are_reported() {
+    let project = SimpleProject::setup();
+    depend_on_dep(&project, Some("patch"));
+    add_app_profile(&project);
+    project.extend_profile(
+        "dep",
+        r#"
+[dependencies.app]
+"#,
+    );
+    let req = std::fs::read_to_string(project.path.join("req.txt")).unwrap();
+
+    let error = match BumpOperation::new("project", "patch").run(&RTContext::at(&project.path)) {
+        Ok(_) => panic!("Cycle is not detected"),
+        Err(error) => error,
+    };
+    assert_eq!(error.payload()[0].1, "project -> dep -> app -> project");
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "patch"]).handle(),
+        111
+    );
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("req.txt")).unwrap(),
+        req
+    );
+
+    // Unknown dependencies matter only for profiles which are affected
+    std::fs::write(
+        project.path.join(".weee/app.version.toml"),
+        "[parts]\n[files]\n[dependencies.lib]\n",
+    )
+    .unwrap();
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "project", "patch", "--read-only"]).handle(),
+        0
+    );
+    std::fs::write(
+        project.path.join(".weee/app.version.toml"),
+        "[parts]\n[files]\n[dependencies.project]\n[dependencies.lib]\n",
+    )
+    .unwrap();
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "project", "patch"]).handle(),
+        110
+    );
+
+    std::fs::write(
+        project.path.join(".weee/app.version.toml"),
+        "[parts]\n[files]\n[dependencies.dep]\nbump = \"patch\"\n",
+    )
+    .unwrap();
+    // The dependency asks for a part the profile does not have
+    assert_eq!(CLI::parse_from(["weee", "validate", "app"]).handle(), 19);
+}
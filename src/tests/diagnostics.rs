@@ -0,0 +1,83 @@
+#![cfg(test)]
+
+use serial_test::serial;
+
+use crate::diagnostics::check_profile;
+use crate::error::Error;
+use crate::tests::utils::simple_project::SimpleProject;
+use crate::RTContext;
+
+#[test]
+fn schema_errors_point_to_the_value() {
+    let content = "[parts.major]\nvalue = 1\nfactory = { name = \"incremnt\" }\n\n[files]\n";
+    let diagnostics = check_profile(content).unwrap_err();
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0]
+        .message
+        .contains("unknown variant `incremnt`"));
+    assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 20));
+    assert_eq!(
+        diagnostics[0].source_line,
+        "factory = { name = \"incremnt\" }"
+    );
+
+    let diagnostics = check_profile("[files]\n").unwrap_err();
+    assert!(diagnostics[0].message.contains("missing field `parts`"));
+}
+
+#[test]
+fn semantic_errors_are_reported_together() {
+    let content = r#"[parts.major]
+value = 1
+factory.name = "increment"
+
+[parts.stage]
+value = "rc"
+factory = { name = "loop", payload = ["alpha", "beta"] }
+
+[parts.build]
+value = "a"
+factory = { name = "loop", payload = [] }
+
+[[files."a.txt"]]
+version.view = "{major}.{minor}{stage}"
+version.placement = "{version}"
+
+[[files."a.txt"]]
+version.view = "{{ major }}.{{ minor }}"
+version.placement = "{version}"
+enable_liquid_tempaltes = true
+"#;
+    let diagnostics = check_profile(content).unwrap_err();
+    let found: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.width))
+        .collect();
+    assert_eq!(found, vec![(6, 9, 4), (11, 38, 2), (14, 25, 7)]);
+    assert!(diagnostics[0].message.contains("`rc`"));
+    assert!(diagnostics[1].message.contains("`build`"));
+    assert!(diagnostics[2].message.contains("`{minor}`"));
+}
+
+#[test]
+#[serial]
+fn invalid_profile_is_not_loaded() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "project",
+        "\n[parts.build]\nvalue = \"c\"\nfactory = { name = \"loop\", payload = [\"a\", \"b\"] }\n",
+    );
+
+    let error = match RTContext::at(&project.path).fetch_profile_conext("project") {
+        Ok(_profile_ctx) => panic!("Profile should be invalid"),
+        Err(err) => err,
+    };
+    assert_eq!(error.exit_code(), 18);
+    match error {
+        Error::InvalidProfileConfig { diagnostics, .. } => {
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(diagnostics[0].source_line, "value = \"c\"");
+        }
+        other => panic!("Unexpected error: {}", other),
+    }
+}
@@ -0,0 +1,392 @@
+#![cfg(test)]
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use serial_test::serial;
+
+use crate::commands::CLI;
+use crate::context::HookStage;
+use crate::error::Error;
+use crate::tests::utils::simple_project::SimpleProject;
+
+fn io_error() -> std::io::Error {
+    std::io::Error::from(std::io::ErrorKind::Other)
+}
+
+/// One value of every variant. `OnFailureHooksFailed` is left out:
+/// it has the exit code of the error it wraps
+fn one_of_each() -> Vec<Error> {
+    vec![
+        Error::WeeeDirectoryAlreadyExists {
+            path: PathBuf::new(),
+        },
+        Error::CannotCreateWeeeDirectory {
+            path: PathBuf::new(),
+            source: io_error(),
+        },
+        Error::ProfileAlreadyExists {
+            profile: String::new(),
+            path: PathBuf::new(),
+        },
+        Error::CannotCreateProfileRule {
+            profile: String::new(),
+            path: PathBuf::new(),
+            source: io_error(),
+        },
+        Error::NoSuchProfileExists {
+            profile: String::new(),
+            path: PathBuf::new(),
+        },
+        Error::TOMLInvalidSyntax {
+            profile: String::new(),
+            path: PathBuf::new(),
+            source: "x =".parse::<toml_edit::Document>().unwrap_err(),
+        },
+        Error::CannotWriteToProfileFile {
+            profile: String::new(),
+            path: PathBuf::new(),
+            source: io_error(),
+        },
+        Error::CannotReadProfile {
+            profile: String::new(),
+            path: PathBuf::new(),
+            source: io_error(),
+        },
+        Error::InvalidProfileConfig {
+            profile: String::new(),
+            path: PathBuf::new(),
+            diagnostics: vec![],
+            sources: vec![],
+        },
+        Error::ValidationFailed {
+            profiles: vec![],
+            problems: 0,
+        },
+        Error::ProfileInheritanceCycle {
+            profile: String::new(),
+            cycle: vec![],
+        },
+        Error::CannotReadWorkspaceFile {
+            path: PathBuf::new(),
+            source: io_error(),
+        },
+        Error::InvalidWorkspaceFile {
+            path: PathBuf::new(),
+            diagnostics: vec![],
+        },
+        Error::NoSuchWorkspaceMember {
+            member: String::new(),
+            members: vec![],
+        },
+        Error::CannotReadGroupsFile {
+            path: PathBuf::new(),
+            source: io_error(),
+        },
+        Error::InvalidGroupsFile {
+            path: PathBuf::new(),
+            diagnostics: vec![],
+        },
+        Error::NoSuchProfileGroup {
+            group: String::new(),
+            groups: vec![],
+        },
+        Error::NoProfileMatchesPattern {
+            pattern: String::new(),
+        },
+        Error::NoSuchDependency {
+            profile: String::new(),
+            dependency: String::new(),
+        },
+        Error::DependencyCycle { cycle: vec![] },
+        Error::NoSuchVersionPartExists {
+            profile: String::new(),
+            part: String::new(),
+        },
+        Error::CannotParsePartValueToInteger {
+            profile: String::new(),
+            part: String::new(),
+            value: String::new(),
+            source: "x".parse::<u64>().unwrap_err(),
+        },
+        Error::LoopFactoryPayloadIsEmpty {
+            profile: String::new(),
+            part: String::new(),
+        },
+        Error::CurrentValueOfLoopedPartDoesNotExist {
+            profile: String::new(),
+            part: String::new(),
+            value: String::new(),
+        },
+        Error::NoSuchFileForReplacements {
+            profile: String::new(),
+            path: PathBuf::new(),
+        },
+        Error::CannotReadReplacementsFileContent {
+            path: PathBuf::new(),
+            source: io_error(),
+        },
+        Error::FileDoesNotContainOldVersion {
+            profile: String::new(),
+            file: String::new(),
+            old_match: String::new(),
+        },
+        Error::NotEnoughOldVersionMatches {
+            profile: String::new(),
+            file: String::new(),
+            old_match: String::new(),
+            expected: 0,
+            found: 0,
+        },
+        Error::CannotWriteToFile {
+            path: PathBuf::new(),
+            source: io_error(),
+        },
+        Error::NoSuchFileView {
+            profile: String::new(),
+            file: String::new(),
+            nth: 0,
+        },
+        Error::ViewCannotBeParsed {
+            profile: String::new(),
+            file: String::new(),
+            view: String::new(),
+        },
+        Error::ChangelogHasNoUnreleasedSection {
+            profile: String::new(),
+            file: String::new(),
+            heading: String::new(),
+        },
+        Error::InvalidTemplateSyntax {
+            profile: String::new(),
+            origin: String::new(),
+            template: String::new(),
+            source: liquid::Error::with_msg("x"),
+        },
+        Error::LiquidTemplateRuntimeError {
+            profile: String::new(),
+            origin: String::new(),
+            template: String::new(),
+            source: liquid::Error::with_msg("x"),
+        },
+        Error::CannotExecuteSubprocess {
+            profile: String::new(),
+            hook: String::new(),
+            stage: HookStage::Before,
+            source: io_error(),
+        },
+        Error::SubproccessCallFailed {
+            profile: String::new(),
+            hook: String::new(),
+            stage: HookStage::Before,
+            attempts: 0,
+            exit_code: None,
+            stderr: String::new(),
+        },
+        Error::HookTimedOut {
+            profile: String::new(),
+            hook: String::new(),
+            stage: HookStage::Before,
+            attempts: 0,
+            timeout_secs: 0,
+            stderr: String::new(),
+        },
+        Error::InvalidHookDefinition {
+            profile: String::new(),
+            hook: String::new(),
+            stage: HookStage::Before,
+            reason: "x",
+        },
+        Error::CannotCreateHookLog {
+            profile: String::new(),
+            hook: String::new(),
+            path: PathBuf::new(),
+            source: io_error(),
+        },
+        Error::CannotFlushStdout { source: io_error() },
+        Error::CannotReadNewValueFromStdin { source: io_error() },
+        Error::InputIsNotAvailable {
+            prompt: String::new(),
+        },
+        Error::CannotRunEditor {
+            editor: String::new(),
+            path: PathBuf::new(),
+            status: None,
+            source: None,
+        },
+        Error::NoSuchEnvironmentVariable {
+            variable: String::new(),
+            source: std::env::VarError::NotPresent,
+        },
+        Error::CannotReadArgumentFile {
+            path: PathBuf::new(),
+            source: io_error(),
+        },
+        Error::GitCommandFailed {
+            command: String::new(),
+            stderr: String::new(),
+            source: None,
+        },
+        Error::WorkingTreeIsDirty { files: vec![] },
+        Error::GitTagAlreadyExists {
+            profile: String::new(),
+            tag: String::new(),
+        },
+        Error::NoMatchingGitTag {
+            profile: String::new(),
+            pattern: String::new(),
+        },
+        Error::NoCommitsToRelease {
+            profile: String::new(),
+            since_tag: None,
+            commits: 0,
+        },
+        Error::GitTagIsNotUnique {
+            tag: String::new(),
+            profiles: vec![],
+        },
+        Error::CannotReadHistory {
+            path: PathBuf::new(),
+            source: io_error(),
+        },
+        Error::CannotWriteHistory {
+            path: PathBuf::new(),
+            source: io_error(),
+        },
+        Error::DifferentGitRepositories { profiles: vec![] },
+        Error::NothingToUndo,
+        Error::VersionChangedSinceBump {
+            profile: String::new(),
+            expected: String::new(),
+            current: String::new(),
+        },
+        Error::FileChangedSinceBump {
+            profile: String::new(),
+            file: String::new(),
+        },
+        Error::InvalidHistoryEntry {
+            path: PathBuf::new(),
+            line: 0,
+            source: serde_json::from_str::<u8>("x").unwrap_err(),
+        },
+    ]
+}
+
+/// Doesn't compile when a variant is added, so it's added to `one_of_each` too
+fn is_listed(err: &Error) -> bool {
+    match err {
+        Error::WeeeDirectoryAlreadyExists { .. }
+        | Error::CannotCreateWeeeDirectory { .. }
+        | Error::ProfileAlreadyExists { .. }
+        | Error::CannotCreateProfileRule { .. }
+        | Error::NoSuchProfileExists { .. }
+        | Error::TOMLInvalidSyntax { .. }
+        | Error::CannotWriteToProfileFile { .. }
+        | Error::CannotReadProfile { .. }
+        | Error::InvalidProfileConfig { .. }
+        | Error::ValidationFailed { .. }
+        | Error::ProfileInheritanceCycle { .. }
+        | Error::CannotReadWorkspaceFile { .. }
+        | Error::InvalidWorkspaceFile { .. }
+        | Error::NoSuchWorkspaceMember { .. }
+        | Error::CannotReadGroupsFile { .. }
+        | Error::InvalidGroupsFile { .. }
+        | Error::NoSuchProfileGroup { .. }
+        | Error::NoProfileMatchesPattern { .. }
+        | Error::NoSuchDependency { .. }
+        | Error::DependencyCycle { .. }
+        | Error::NoSuchVersionPartExists { .. }
+        | Error::CannotParsePartValueToInteger { .. }
+        | Error::LoopFactoryPayloadIsEmpty { .. }
+        | Error::CurrentValueOfLoopedPartDoesNotExist { .. }
+        | Error::NoSuchFileForReplacements { .. }
+        | Error::CannotReadReplacementsFileContent { .. }
+        | Error::FileDoesNotContainOldVersion { .. }
+        | Error::NotEnoughOldVersionMatches { .. }
+        | Error::CannotWriteToFile { .. }
+        | Error::NoSuchFileView { .. }
+        | Error::ViewCannotBeParsed { .. }
+        | Error::ChangelogHasNoUnreleasedSection { .. }
+        | Error::InvalidTemplateSyntax { .. }
+        | Error::LiquidTemplateRuntimeError { .. }
+        | Error::CannotExecuteSubprocess { .. }
+        | Error::SubproccessCallFailed { .. }
+        | Error::HookTimedOut { .. }
+        | Error::InvalidHookDefinition { .. }
+        | Error::CannotCreateHookLog { .. }
+        | Error::CannotFlushStdout { .. }
+        | Error::CannotReadNewValueFromStdin { .. }
+        | Error::InputIsNotAvailable { .. }
+        | Error::CannotRunEditor { .. }
+        | Error::NoSuchEnvironmentVariable { .. }
+        | Error::CannotReadArgumentFile { .. }
+        | Error::GitCommandFailed { .. }
+        | Error::WorkingTreeIsDirty { .. }
+        | Error::GitTagAlreadyExists { .. }
+        | Error::NoMatchingGitTag { .. }
+        | Error::NoCommitsToRelease { .. }
+        | Error::GitTagIsNotUnique { .. }
+        | Error::CannotReadHistory { .. }
+        | Error::CannotWriteHistory { .. }
+        | Error::DifferentGitRepositories { .. }
+        | Error::NothingToUndo
+        | Error::VersionChangedSinceBump { .. }
+        | Error::FileChangedSinceBump { .. }
+        | Error::InvalidHistoryEntry { .. } => true,
+        Error::OnFailureHooksFailed { .. } => false,
+    }
+}
+
+#[test]
+fn every_error_has_own_exit_code() {
+    let mut codes = std::collections::HashMap::new();
+    for err in one_of_each() {
+        assert!(is_listed(&err));
+        let code = err.exit_code();
+        assert_ne!(code, 1, "{} has no dedicated exit code", err.title());
+        if let Some(other) = codes.insert(code, err.title()) {
+            panic!("{} and {} share exit code {}", err.title(), other, code);
+        }
+    }
+}
+
+#[test]
+fn failed_recovery_keeps_original_error() {
+    let err = Error::OnFailureHooksFailed {
+        error: Box::new(Error::NothingToUndo),
+        on_failure_error: Box::new(Error::WorkingTreeIsDirty {
+            files: vec!["a.txt".into()],
+        }),
+    };
+    assert_eq!(err.title(), "NothingToUndo");
+    assert_eq!(err.exit_code(), 82);
+    assert_eq!(err.payload()[0].0, "on_failure_hooks_error");
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+#[test]
+#[serial]
+fn exit_code_of_failed_command() {
+    SimpleProject::setup();
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "project", "major"]).handle(),
+        0
+    );
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "project", "unknown"]).handle(),
+        20
+    );
+}
+
+#[test]
+#[serial]
+fn json_error_format() {
+    let project = SimpleProject::setup();
+    let output = project.run_binary(&["show", "unknown", "--error-format", "json"]);
+    assert_eq!(output.status.code(), Some(14));
+
+    let document: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(document["title"], "NoSuchProfileExists");
+    assert_eq!(document["payload"]["profile"], "unknown");
+    assert_eq!(document["exit_code"], 14);
+}
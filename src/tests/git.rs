@@ -0,0 +1,351 @@
+#![cfg(test)]
+#![cfg(unix)]
+
+use clap::Parser;
+use serial_test::serial;
+
+use crate::commands::CLI;
+use crate::tests::utils::simple_project::SimpleProject;
+
+#[test]
+#[serial]
+fn commit_and_annotated_tag() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[git]
+require_clean = true
+commit = true
+tag = "v{{ new_version }}"
+tag_message = "Release {{ new_version }}"
+"#,
+    );
+    project.init_git();
+    // Files managed by the profile are allowed to be dirty
+    std::fs::write(
+        project.path.join("req.txt"),
+        "dep==0.1.0-alpha0\ndep-another-style==0.1\n# note\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        0
+    );
+    assert_eq!(
+        project.git(&["log", "-1", "--format=%s"]),
+        "Bump version: 0.1.0-alpha0 → 1.0.0-alpha0"
+    );
+    assert_eq!(project.git(&["cat-file", "-t", "v1.0.0-alpha0"]), "tag");
+    assert_eq!(
+        project.git(&["status", "--porcelain", "--", ".", ":!.weee/history.jsonl"]),
+        ""
+    );
+    assert_eq!(project.fetch_versions().dep, "1.0.0-alpha0");
+}
+
+#[test]
+#[serial]
+fn only_bumped_files_are_committed() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[git]
+commit = true
+commit_message = "Release {{ new.major }}"
+tag = "dep-{{ new_version }}"
+"#,
+    );
+    project.init_git();
+    std::fs::write(project.path.join("unrelated.txt"), "work in progress").unwrap();
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        0
+    );
+    assert_eq!(project.git(&["log", "-1", "--format=%s"]), "Release 1");
+    assert_eq!(
+        project.git(&["show", "--name-only", "--format=", "HEAD"]),
+        ".weee/dep.version.toml\nreq.txt"
+    );
+    assert_eq!(
+        project.git(&["cat-file", "-t", "dep-1.0.0-alpha0"]),
+        "commit"
+    );
+    assert_eq!(
+        project.git(&["status", "--porcelain", "--", ".", ":!.weee/history.jsonl"]),
+        "?? unrelated.txt"
+    );
+}
+
+#[test]
+#[serial]
+fn dirty_tree_is_refused() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[git]
+require_clean = true
+commit = true
+"#,
+    );
+    project.init_git();
+    std::fs::write(project.path.join("pyproject.toml"), "changed").unwrap();
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        71
+    );
+    assert!(std::fs::read_to_string(project.path.join("req.txt"))
+        .unwrap()
+        .contains("dep==0.1.0-alpha0"));
+    assert_eq!(project.git(&["rev-list", "--count", "HEAD"]), "1");
+}
+
+#[test]
+#[serial]
+fn existing_tag_is_refused_before_changes() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[git]
+commit = true
+tag = "v{{ new_version }}"
+"#,
+    );
+    project.init_git();
+    project.git(&["tag", "v1.0.0-alpha0"]);
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        72
+    );
+    assert_eq!(project.fetch_versions().dep, "0.1.0-alpha0");
+    assert_eq!(
+        project.git(&["status", "--porcelain", "--", ".", ":!.weee/history.jsonl"]),
+        ""
+    );
+}
+
+#[test]
+#[serial]
+fn sync_from_highest_tag() {
+    let project = SimpleProject::setup();
+    project.init_git();
+    for tag in [
+        "v14.23.5645b3",
+        "v14.23.10000a0",
+        "v14.9.99999b9",
+        "vnext",
+        "release-15.0.0a0",
+    ] {
+        project.git(&["tag", tag]);
+    }
+
+    assert_eq!(
+        CLI::parse_from([
+            "weee",
+            "sync",
+            "project",
+            "--from-git-tag",
+            "--pattern",
+            "v*"
+        ])
+        .handle(),
+        0
+    );
+    let output = project.run_binary(&["show", "project", "--file", "pyproject.toml"]);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "14.23.10000a0\n");
+    // Files are not touched
+    assert_eq!(project.fetch_versions().project, "14.23.5645b3");
+}
+
+#[test]
+#[serial]
+fn sync_json_and_missing_tag() {
+    let project = SimpleProject::setup();
+    project.init_git();
+    project.git(&["tag", "v0.2.0-beta1"]);
+
+    let output = project.run_binary(&[
+        "sync",
+        "dep",
+        "--from-git-tag",
+        "--read-only",
+        "--output",
+        "json",
+    ]);
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["tag"], "v0.2.0-beta1");
+    assert_eq!(document["changed"], true);
+    assert_eq!(document["new"]["parts"]["stage"], "beta");
+    assert_eq!(document["new"]["parts"]["step"], 1);
+    let output = project.run_binary(&["show", "dep"]);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "0.1.0.alpha.0\n");
+
+    assert_eq!(
+        CLI::parse_from([
+            "weee",
+            "sync",
+            "dep",
+            "--from-git-tag",
+            "--pattern",
+            "release-*"
+        ])
+        .handle(),
+        73
+    );
+}
+
+fn commit(project: &SimpleProject, message: &str) {
+    project.git(&["commit", "--quiet", "--allow-empty", "-m", message]);
+}
+
+#[test]
+#[serial]
+fn auto_bump_picks_most_significant_part() {
+    let project = SimpleProject::setup();
+    project.init_git();
+    project.git(&["tag", "v0.1.0"]);
+    commit(&project, "fix(parser): handle empty input");
+    commit(&project, "feat: add a flag");
+    commit(&project, "chore: update deps");
+
+    let output = project.run_binary(&["bump", "dep", "auto", "--output", "json"]);
+    assert_eq!(output.status.code(), Some(0));
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["bumped_part"], "minor");
+    assert_eq!(document["auto"]["since_tag"], "v0.1.0");
+    assert_eq!(
+        document["auto"]["commits"][0]["part"],
+        serde_json::Value::Null
+    );
+    assert_eq!(document["auto"]["commits"][1]["part"], "minor");
+    assert_eq!(document["auto"]["commits"][2]["part"], "patch");
+    assert_eq!(project.fetch_versions().dep, "0.2.0-alpha0");
+
+    // The next release only sees commits after the new tag
+    project.git(&["tag", "v0.2.0"]);
+    commit(&project, "refactor!: drop the old API");
+    assert_eq!(CLI::parse_from(["weee", "bump", "dep", "auto"]).handle(), 0);
+    assert_eq!(project.fetch_versions().dep, "1.0.0-alpha0");
+}
+
+#[test]
+#[serial]
+fn auto_bump_with_custom_mapping() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[conventional_commits]
+types = { perf = "step", fix = "step" }
+breaking = "minor"
+tag_pattern = "dep-*"
+"#,
+    );
+    project.init_git();
+    project.git(&["tag", "dep-0.1.0"]);
+    commit(&project, "feat: not mapped anymore");
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "auto"]).handle(),
+        74
+    );
+
+    commit(
+        &project,
+        "perf: faster\n\nBREAKING CHANGE: needs more memory",
+    );
+    commit(&project, "fix: typo");
+    assert_eq!(CLI::parse_from(["weee", "bump", "dep", "auto"]).handle(), 0);
+    assert_eq!(project.fetch_versions().dep, "0.2.0-alpha0");
+}
+
+#[test]
+#[serial]
+fn auto_bump_skips_tags_which_are_not_versions() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        "\n[git]\ncommit = true\ntag = \"dep-{{ new_version }}\"\n",
+    );
+    project.init_git();
+    project.git(&["tag", "dep-0.1.0-alpha0"]);
+    commit(&project, "feat: add a flag");
+    project.git(&["tag", "docs-published"]);
+    commit(&project, "fix: typo");
+
+    let output = project.run_binary(&["bump", "dep", "auto", "--output", "json"]);
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["auto"]["since_tag"], "dep-0.1.0-alpha0");
+    assert_eq!(document["bumped_part"], "minor");
+}
+
+#[test]
+#[serial]
+fn auto_bump_ignores_unreachable_tags() {
+    let project = SimpleProject::setup();
+    project.init_git();
+    project.git(&["branch", "feature"]);
+    commit(&project, "fix: released one");
+    project.git(&["tag", "v0.1.0"]);
+    project.git(&["checkout", "--quiet", "feature"]);
+    commit(&project, "feat: made before the release");
+
+    let output = project.run_binary(&["bump", "dep", "auto", "--output", "json"]);
+    assert_eq!(output.status.code(), Some(0));
+    let document: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(document["auto"]["since_tag"], serde_json::Value::Null);
+    assert_eq!(document["bumped_part"], "minor");
+}
+
+#[test]
+#[serial]
+fn history_does_not_make_tree_dirty() {
+    let project = SimpleProject::setup();
+    project.extend_profile("dep", "\n[git]\nrequire_clean = true\ncommit = true\n");
+    project.init_git();
+
+    for _ in 0..2 {
+        assert_eq!(
+            CLI::parse_from(["weee", "bump", "dep", "patch"]).handle(),
+            0
+        );
+    }
+    assert_eq!(project.git(&["rev-list", "--count", "HEAD"]), "3");
+}
+
+#[test]
+#[serial]
+fn hook_logs_do_not_make_tree_dirty() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[git]
+require_clean = true
+commit = true
+
+[hooks]
+save_logs = true
+
+[hooks.afterwords]
+greet = ["echo", "hello"]
+"#,
+    );
+    project.init_git();
+
+    for _ in 0..2 {
+        assert_eq!(
+            CLI::parse_from(["weee", "bump", "dep", "patch"]).handle(),
+            0
+        );
+    }
+    assert!(project.path.join(".weee/logs").is_dir());
+    assert_eq!(project.git(&["rev-list", "--count", "HEAD"]), "3");
+}
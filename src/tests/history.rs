@@ -0,0 +1,149 @@
+#![cfg(test)]
+
+use clap::Parser;
+use serial_test::serial;
+
+use crate::commands::CLI;
+use crate::tests::utils::simple_project::SimpleProject;
+
+#[test]
+#[serial]
+fn bumps_are_recorded_and_undone() {
+    let project = SimpleProject::setup();
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        0
+    );
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "project", "stage"]).handle(),
+        0
+    );
+    // Read-only runs are not recorded
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "project", "major", "--read-only"]).handle(),
+        0
+    );
+
+    let output = project.run_binary(&["history", "--output", "json"]);
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(entries.as_array().unwrap().len(), 2);
+    assert_eq!(entries[0]["action"], "bump");
+    assert_eq!(entries[0]["profile"], "dep");
+    assert_eq!(entries[0]["bumped_part"], "major");
+    assert_eq!(entries[0]["old"]["parts"]["major"], 0);
+    assert_eq!(entries[0]["new"]["parts"]["major"], 1);
+    assert_eq!(entries[0]["files"][0]["path"], "req.txt");
+    assert_eq!(entries[0]["files"][0]["sha256"].as_str().unwrap().len(), 64);
+
+    let output = project.run_binary(&["history", "project", "--output", "json"]);
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(entries.as_array().unwrap().len(), 1);
+
+    assert_eq!(CLI::parse_from(["weee", "undo"]).handle(), 0);
+    assert_eq!(project.fetch_versions().project, "14.23.5645b3");
+    assert_eq!(project.fetch_versions().dep, "1.0.0-alpha0");
+    assert_eq!(CLI::parse_from(["weee", "undo"]).handle(), 0);
+    assert_eq!(
+        SimpleProject::untouched_versions(),
+        project.fetch_versions()
+    );
+    let output = project.run_binary(&["show", "dep"]);
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "0.1.0.alpha.0\n");
+
+    assert_eq!(CLI::parse_from(["weee", "undo"]).handle(), 82);
+}
+
+#[test]
+#[serial]
+fn undo_refuses_changed_files() {
+    let project = SimpleProject::setup();
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "minor"]).handle(),
+        0
+    );
+    let req = project.path.join("req.txt");
+    let content = std::fs::read_to_string(&req).unwrap();
+    std::fs::write(&req, format!("{}other==1.0\n", content)).unwrap();
+
+    assert_eq!(CLI::parse_from(["weee", "undo"]).handle(), 84);
+    assert_eq!(project.fetch_versions().dep, "0.2.0-alpha0");
+
+    std::fs::write(&req, content).unwrap();
+    assert_eq!(CLI::parse_from(["weee", "undo"]).handle(), 0);
+    assert_eq!(
+        SimpleProject::untouched_versions(),
+        project.fetch_versions()
+    );
+}
+
+#[test]
+#[serial]
+fn undo_reverts_changelog() {
+    let project = SimpleProject::setup();
+    let changelog = "# Changelog\n\n## [Unreleased]\n\n- Entry\n";
+    std::fs::write(project.path.join("CHANGELOG.md"), changelog).unwrap();
+    project.extend_profile(
+        "dep",
+        "\n[changelog]\nheading = \"## {{ new.major }}.{{ new.minor }}\"\n",
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "minor"]).handle(),
+        0
+    );
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("CHANGELOG.md")).unwrap(),
+        "# Changelog\n\n## [Unreleased]\n\n## 0.2\n\n- Entry\n"
+    );
+
+    assert_eq!(CLI::parse_from(["weee", "undo"]).handle(), 0);
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("CHANGELOG.md")).unwrap(),
+        changelog
+    );
+    assert_eq!(
+        SimpleProject::untouched_versions(),
+        project.fetch_versions()
+    );
+}
+
+#[test]
+#[serial]
+fn profiles_bumped_together_are_undone_together() {
+    let project = SimpleProject::setup();
+    // `req.txt` is shared, so its hash is the same for both profiles
+    project.extend_profile(
+        "project",
+        r#"
+
+[[files."req.txt"]]
+version.view = "{major}.{minor}.{patch}{stage}{step}"
+version.placement = "project=={version}"
+"#,
+    );
+    std::fs::write(
+        project.path.join("req.txt"),
+        "dep==0.1.0-alpha0\ndep-another-style==0.1\nproject==14.23.5645b3\n",
+    )
+    .unwrap();
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "patch"]).handle(),
+        0
+    );
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "--profiles", "dep,project", "minor"]).handle(),
+        0
+    );
+
+    assert_eq!(CLI::parse_from(["weee", "undo"]).handle(), 0);
+    assert_eq!(project.fetch_versions().dep, "0.1.1-alpha0");
+    assert_eq!(project.fetch_versions().project, "14.23.5645b3");
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("req.txt")).unwrap(),
+        "dep==0.1.1-alpha0\ndep-another-style==0.1\nproject==14.23.5645b3\n"
+    );
+
+    assert_eq!(CLI::parse_from(["weee", "undo"]).handle(), 0);
+    assert_eq!(project.fetch_versions().dep, "0.1.0-alpha0");
+    assert_eq!(CLI::parse_from(["weee", "undo"]).handle(), 82);
+}
@@ -0,0 +1,474 @@
+#![cfg(test)]
+#![cfg(unix)]
+
+use clap::Parser;
+use serial_test::serial;
+
+use crate::commands::CLI;
+use crate::tests::utils::simple_project::SimpleProject;
+
+#[test]
+#[serial]
+fn before_hooks_see_old_files() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        &format!(
+            r#"
+[hooks.before]
+check = ["grep", "-q", "dep==0.1.0-alpha0", "{}"]
+"#,
+            project.path.join("req.txt").display()
+        ),
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        0
+    );
+    assert_eq!(project.fetch_versions().dep, "1.0.0-alpha0");
+}
+
+#[test]
+#[serial]
+fn failed_before_hook_aborts_bump() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[hooks.before]
+lint = ["false"]
+
+[hooks.afterwords]
+never = ["false"]
+"#,
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        51
+    );
+    assert_eq!(
+        SimpleProject::untouched_versions(),
+        project.fetch_versions()
+    );
+}
+
+#[test]
+#[serial]
+fn failed_afterword_hook_runs_on_failure_hooks() {
+    let project = SimpleProject::setup();
+    let marker = project.path.join("failed");
+    project.extend_profile(
+        "dep",
+        &format!(
+            r#"
+[hooks.afterwords]
+tag = ["false"]
+
+[hooks.on_failure]
+cleanup = ["touch", "{}"]
+"#,
+            marker.display()
+        ),
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        51
+    );
+    assert!(marker.exists());
+}
+
+#[test]
+#[serial]
+fn failed_replacement_runs_on_failure_hooks() {
+    let project = SimpleProject::setup();
+    let marker = project.path.join("failed");
+    std::fs::write(project.path.join("req.txt"), "dep==0.1.0-alpha0\n").unwrap();
+    project.extend_profile(
+        "dep",
+        &format!(
+            r#"
+[hooks.on_failure]
+cleanup = ["touch", "{}"]
+"#,
+            marker.display()
+        ),
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        32
+    );
+    assert!(marker.exists());
+    // The first pattern matched, but nothing is written since the second one failed
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("req.txt")).unwrap(),
+        "dep==0.1.0-alpha0\n"
+    );
+}
+
+#[test]
+#[serial]
+fn conditional_hooks() {
+    let project = SimpleProject::setup();
+    let marker = |name: &str| project.path.join(name);
+    project.extend_profile(
+        "dep",
+        &format!(
+            r#"
+[hooks.afterwords]
+plain = ["touch", "{plain}"]
+on-major = {{ args = ["touch", "{major}"], only_parts = ["major"] }}
+on-minor = {{ args = ["touch", "{minor}"], only_parts = ["minor"] }}
+on-beta = {{ args = ["touch", "{beta}"], when = "{{{{ new.stage == 'beta' }}}}" }}
+on-other-profile = {{ args = ["touch", "{other}"], only_profiles = ["project"] }}
+"#,
+            plain = marker("plain").display(),
+            major = marker("major").display(),
+            minor = marker("minor").display(),
+            beta = marker("beta").display(),
+            other = marker("other").display(),
+        ),
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "minor"]).handle(),
+        0
+    );
+    assert!(marker("plain").exists());
+    assert!(marker("minor").exists());
+    assert!(!marker("major").exists());
+    assert!(!marker("beta").exists());
+    assert!(!marker("other").exists());
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "stage"]).handle(),
+        0
+    );
+    assert!(marker("beta").exists());
+    assert!(!marker("major").exists());
+}
+
+#[test]
+#[serial]
+fn hooks_skipped_in_read_only_mode() {
+    let project = SimpleProject::setup();
+    let tagged = project.path.join("tagged");
+    let checked = project.path.join("checked");
+    project.extend_profile(
+        "dep",
+        &format!(
+            r#"
+[hooks.afterwords]
+check = ["touch", "{}"]
+tag = {{ args = ["touch", "{}"], skip_in_read_only = true }}
+"#,
+            checked.display(),
+            tagged.display()
+        ),
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major", "--read-only"]).handle(),
+        0
+    );
+    assert!(checked.exists());
+    assert!(!tagged.exists());
+}
+
+#[test]
+#[serial]
+fn hooks_get_version_in_environment() {
+    let project = SimpleProject::setup();
+    let dump = project.path.join("env.txt");
+    project.extend_profile(
+        "dep",
+        &format!(
+            r#"
+[hooks.afterwords]
+dump = ["sh", "-c", "env | grep ^WEEE_ | sort > {}"]
+"#,
+            dump.display()
+        ),
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "minor"]).handle(),
+        0
+    );
+    let env = std::fs::read_to_string(dump).unwrap();
+    for line in [
+        "WEEE_PROFILE=dep",
+        "WEEE_BUMPED_PART=minor",
+        "WEEE_OLD_VERSION=0.1.0-alpha0",
+        "WEEE_NEW_VERSION=0.2.0-alpha0",
+        "WEEE_OLD_MINOR=1",
+        "WEEE_NEW_MINOR=2",
+        "WEEE_NEW_STAGE=alpha",
+        "WEEE_READ_ONLY=0",
+    ] {
+        assert!(env.lines().any(|env_line| env_line == line), "{}", line);
+    }
+}
+
+#[test]
+#[serial]
+fn shell_hooks_with_cwd_and_env() {
+    let project = SimpleProject::setup();
+    std::fs::create_dir(project.path.join("build")).unwrap();
+    project.extend_profile(
+        "dep",
+        r#"
+[hooks.afterwords.notes]
+shell = "echo \"$GREETING $WEEE_NEW_MAJOR\" | tr a-z A-Z > notes.txt"
+cwd = "build"
+env = { GREETING = "released" }
+"#,
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        0
+    );
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("build").join("notes.txt")).unwrap(),
+        "RELEASED 1\n"
+    );
+}
+
+#[test]
+#[serial]
+fn hook_timeout() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[hooks.before.hangs]
+shell = "sleep 10"
+timeout_secs = 1
+"#,
+    );
+
+    let started = std::time::Instant::now();
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        52
+    );
+    assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(
+        SimpleProject::untouched_versions(),
+        project.fetch_versions()
+    );
+}
+
+#[test]
+#[serial]
+fn allowed_failures_and_retries() {
+    let project = SimpleProject::setup();
+    let finished = project.path.join("finished");
+    project.extend_profile(
+        "dep",
+        &format!(
+            r#"
+[hooks.afterwords]
+flaky = {{ shell = "test -f flaky || {{ touch flaky; exit 1; }}", retries = 1 }}
+broken = {{ args = ["false"], allow_failure = true }}
+finish = ["touch", "{}"]
+"#,
+            finished.display()
+        ),
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        0
+    );
+    assert!(finished.exists());
+}
+
+#[test]
+#[serial]
+fn invalid_hook_definition() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[hooks.before.both]
+args = ["true"]
+shell = "true"
+"#,
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        53
+    );
+}
+
+#[test]
+#[serial]
+fn hook_output_tail_and_logs() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[hooks]
+save_logs = true
+
+[hooks.afterwords.count]
+shell = "seq 1 150; echo failed >&2"
+"#,
+    );
+
+    let output = project.run_binary(&["bump", "dep", "major", "--output", "json"]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let hook = &report["hooks"][0];
+
+    let stdout_tail: Vec<&str> = hook["stdout"].as_str().unwrap().lines().collect();
+    assert_eq!(stdout_tail.len(), crate::process::TAIL_LINES);
+    assert_eq!(stdout_tail.last(), Some(&"150"));
+    assert_eq!(hook["stderr"], "failed\n");
+
+    let log = std::fs::read_to_string(hook["log"].as_str().unwrap()).unwrap();
+    let log_lines: Vec<&str> = log.lines().collect();
+    // Streams are interleaved in the log, so only their content is checked
+    assert_eq!(log_lines.len(), 151);
+    assert!(log_lines.contains(&"1"));
+    assert!(log_lines.contains(&"failed"));
+    assert!(project.path.join(".weee").join("logs").is_dir());
+}
+
+#[test]
+#[serial]
+fn liquid_hook_arguments() {
+    let project = SimpleProject::setup();
+    std::env::set_var("WEEE_TEST_AUTHOR", "tester");
+    project.extend_profile(
+        "dep",
+        r#"
+[hooks.afterwords.message]
+args = [
+    "sh", "-c", "printf '%s' \"$1\" > message.txt", "sh",
+    "!FORMAT:{{ profile | upcase }} {{ old.major }}.{{ old.minor }} -> {{ new.major }}.{{ new.minor }}{% if new.stage != 'final' %}-{{ new.stage }}{% endif %} ({{ bumped_part }}) by {{ env.WEEE_TEST_AUTHOR }} at {{ date.now | date: '%Y' }}",
+]
+"#,
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "minor"]).handle(),
+        0
+    );
+    let year = liquid::model::DateTime::now().year();
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("message.txt")).unwrap(),
+        format!("DEP 0.1 -> 0.2-alpha (minor) by tester at {}", year)
+    );
+}
+
+#[test]
+#[serial]
+fn plain_format_hook_arguments() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[hooks.afterwords.message]
+args = ["sh", "-c", "printf '%s' \"$1\" > message.txt", "sh", "!FORMAT:v{new.major}.{new.minor} after {old.minor}"]
+"#,
+    );
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "minor"]).handle(),
+        0
+    );
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("message.txt")).unwrap(),
+        "v0.2 after 1"
+    );
+}
+
+fn write_args_hook(project: &SimpleProject, args: &str) {
+    project.extend_profile(
+        "dep",
+        &format!(
+            r#"
+[hooks.afterwords.args]
+args = ["sh", "-c", "printf '%s;' \"$@\" > args.txt", "sh", {}]
+"#,
+            args
+        ),
+    );
+}
+
+fn read_args(project: &SimpleProject) -> String {
+    std::fs::read_to_string(project.path.join("args.txt")).unwrap()
+}
+
+#[test]
+#[serial]
+fn non_interactive_argument_sources() {
+    let project = SimpleProject::setup();
+    std::fs::write(project.path.join("notes.md"), "Some notes\r\n").unwrap();
+    std::env::set_var("WEEE_TEST_TOKEN", "secret");
+    std::env::remove_var("WEEE_TEST_MISSING");
+    write_args_hook(
+        &project,
+        r#""!ASK:Tag message|released", "!EDITOR:Release notes|no notes", "!ENV:WEEE_TEST_TOKEN", "!ENV:WEEE_TEST_MISSING|fallback", "!FILE:notes.md""#,
+    );
+
+    // Stdin is closed for the binary, so defaults are used
+    let output = project.run_binary(&["bump", "dep", "major"]);
+    assert!(output.status.success());
+    assert_eq!(
+        read_args(&project),
+        "released;no notes;secret;fallback;Some notes;"
+    );
+}
+
+#[test]
+#[serial]
+fn asked_value_without_default_and_input() {
+    let project = SimpleProject::setup();
+    write_args_hook(&project, r#""!ASK:Tag message""#);
+
+    let output = project.run_binary(&["bump", "dep", "major"]);
+    assert_eq!(output.status.code(), Some(62));
+}
+
+#[test]
+#[serial]
+fn asked_values_are_piped() {
+    let project = SimpleProject::setup();
+    write_args_hook(
+        &project,
+        r#""!ASK:First", "!ASK:Second|default", "!ASK:Third|default""#,
+    );
+
+    let mut child = std::process::Command::new("cargo")
+        .args(["run", "-q", "--", "bump", "dep", "major"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let mut child_stdin = child.stdin.take().unwrap();
+    std::io::Write::write_all(&mut child_stdin, b"windows\r\n\r\nlast").unwrap();
+    drop(child_stdin);
+    assert!(child.wait().unwrap().success());
+
+    assert_eq!(read_args(&project), "windows;default;last;");
+}
+
+#[test]
+#[serial]
+fn missing_environment_variable() {
+    let project = SimpleProject::setup();
+    std::env::remove_var("WEEE_TEST_MISSING");
+    write_args_hook(&project, r#""!ENV:WEEE_TEST_MISSING""#);
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "major"]).handle(),
+        64
+    );
+}
@@ -0,0 +1,53 @@
+#![cfg(test)]
+
+use serial_test::serial;
+
+use crate::config::IntegerOrString;
+use crate::tests::utils::simple_project::SimpleProject;
+use crate::{BumpOperation, CheckOperation, MoveOperation, RTContext};
+
+#[test]
+#[serial]
+fn operations_return_reports() {
+    let project = SimpleProject::setup();
+    let ctx = RTContext::at(&project.path);
+
+    let report = BumpOperation::new("dep", "minor")
+        .read_only(true)
+        .run(&ctx)
+        .unwrap();
+    assert_eq!(report.new.version, "0.2.0.alpha.0");
+    assert_eq!(report.files.len(), 2);
+    assert_eq!(project.fetch_versions().dep, "0.1.0-alpha0");
+
+    let report = MoveOperation::new("project")
+        .part("major", IntegerOrString::Integer(15))
+        .part("stage", IntegerOrString::String("a".into()))
+        .run(&ctx)
+        .unwrap();
+    assert_eq!(report.old.version, "14.23.5645.b.3");
+    assert_eq!(report.new.version, "15.23.5645.a.3");
+    assert_eq!(project.fetch_versions().project, "15.23.5645a3");
+
+    let error = MoveOperation::new("project")
+        .part("unknown", IntegerOrString::Integer(1))
+        .run(&ctx)
+        .unwrap_err();
+    assert_eq!(error.title(), "NoSuchVersionPartExists");
+}
+
+#[test]
+#[serial]
+fn check_finds_current_version() {
+    let project = SimpleProject::setup();
+    let ctx = RTContext::at(&project.path);
+
+    let reports = CheckOperation::new("dep").run(&ctx).unwrap();
+    assert_eq!(reports[0].path, "req.txt");
+    assert_eq!(reports[0].lines, vec![1]);
+    assert_eq!(reports[1].lines, vec![2]);
+
+    std::fs::write(project.path.join("req.txt"), "dep==0.3.0-alpha0\n").unwrap();
+    let error = CheckOperation::new("dep").run(&ctx).unwrap_err();
+    assert_eq!(error.title(), "FileDoesNotContainOldVersion");
+}
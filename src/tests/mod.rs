@@ -1,3 +1,17 @@
+pub mod changelog;
 pub mod check;
 pub mod commands;
+pub mod composition;
+pub mod dependencies;
+pub mod diagnostics;
+pub mod exit_codes;
+pub mod git;
+pub mod history;
+pub mod hooks;
+pub mod library;
+pub mod multi_bump;
+pub mod output;
+pub mod schema;
 pub mod utils;
+pub mod validate;
+pub mod workspace;
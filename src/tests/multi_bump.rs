@@ -0,0 +1,216 @@
+#![cfg(test)]
+#![cfg(unix)]
+
+use clap::Parser;
+use serial_test::serial;
+
+use crate::commands::CLI;
+use crate::tests::utils::simple_project::SimpleProject;
+use crate::{MultiBumpOperation, RTContext};
+
+/// `req.txt` is edited by both `dep` and `project`
+fn share_requirements(project: &SimpleProject) {
+    project.extend_profile(
+        "project",
+        r#"
+
+[[files."req.txt"]]
+version.view = "{major}.{minor}.{patch}{stage}{step}"
+version.placement = "project=={version}"
+"#,
+    );
+    std::fs::write(
+        project.path.join("req.txt"),
+        "dep==0.1.0-alpha0\ndep-another-style==0.1\nproject==14.23.5645b3\n",
+    )
+    .unwrap();
+}
+
+#[test]
+#[serial]
+fn shared_file_gets_all_changes() {
+    let project = SimpleProject::setup();
+    share_requirements(&project);
+
+    let reports = MultiBumpOperation::new(["dep", "project"], "minor")
+        .run(&RTContext::at(&project.path))
+        .unwrap();
+    let profiles: Vec<_> = reports
+        .iter()
+        .map(|report| report.profile.as_str())
+        .collect();
+    assert_eq!(profiles, vec!["dep", "project"]);
+
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("req.txt")).unwrap(),
+        "dep==0.2.0-alpha0\ndep-another-style==0.2\nproject==14.24.0a0\n"
+    );
+    assert_eq!(project.fetch_versions().project, "14.24.0a0");
+}
+
+#[test]
+#[serial]
+fn nothing_is_changed_if_one_profile_fails() {
+    let project = SimpleProject::setup();
+    share_requirements(&project);
+    project.extend_profile(
+        "project",
+        r#"
+[parts.build]
+value = 0
+factory.name = "increment"
+"#,
+    );
+    let dep_profile = std::fs::read_to_string(project.path.join(".weee/dep.version.toml")).unwrap();
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "--profiles", "project,dep", "build"]).handle(),
+        20
+    );
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("req.txt")).unwrap(),
+        "dep==0.1.0-alpha0\ndep-another-style==0.1\nproject==14.23.5645b3\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(project.path.join(".weee/dep.version.toml")).unwrap(),
+        dep_profile
+    );
+}
+
+#[test]
+#[serial]
+fn group_is_committed_and_tagged_once() {
+    let project = SimpleProject::setup();
+    let marker = project.path.join("notified");
+    for (profile, tag) in [
+        ("dep", "dep-{{ new_version }}"),
+        ("project", "v{{ new_version }}"),
+    ] {
+        project.extend_profile(
+            profile,
+            &format!(
+                r#"
+[git]
+commit = true
+tag = "{}"
+
+[hooks.afterwords]
+notify = ["sh", "-c", "echo bumped >> {}"]
+"#,
+                tag,
+                marker.display()
+            ),
+        );
+    }
+    std::fs::write(
+        project.path.join(".weee/groups.toml"),
+        "release = [\"project\", \"dep\"]\n",
+    )
+    .unwrap();
+    project.init_git();
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "--group", "release", "patch"]).handle(),
+        0
+    );
+    assert_eq!(project.git(&["rev-list", "--count", "HEAD"]), "2");
+    assert_eq!(
+        project.git(&["show", "--name-only", "--format=", "HEAD"]),
+        ".weee/dep.version.toml\n.weee/project.version.toml\npyproject.toml\nreq.txt"
+    );
+    assert_eq!(
+        project.git(&["tag", "--points-at", "HEAD"]),
+        "dep-0.1.1-alpha0\nv14.23.5646a0"
+    );
+    assert_eq!(std::fs::read_to_string(&marker).unwrap(), "bumped\n");
+}
+
+#[test]
+#[serial]
+fn unknown_group_is_reported() {
+    let project = SimpleProject::setup();
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "--group", "release", "patch"]).handle(),
+        102
+    );
+
+    std::fs::write(
+        project.path.join(".weee/groups.toml"),
+        "release = \"dep\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "--group", "release", "patch"]).handle(),
+        101
+    );
+}
+
+#[test]
+#[serial]
+fn same_tag_of_two_profiles_is_reported() {
+    let project = SimpleProject::setup();
+    for profile in ["dep", "project"] {
+        project.extend_profile(
+            profile,
+            r#"
+[git]
+commit = true
+tag = "release"
+"#,
+        );
+    }
+    project.init_git();
+    let req = std::fs::read_to_string(project.path.join("req.txt")).unwrap();
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "--profiles", "dep,project", "patch"]).handle(),
+        75
+    );
+    assert_eq!(project.git(&["rev-list", "--count", "HEAD"]), "1");
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("req.txt")).unwrap(),
+        req
+    );
+}
+
+#[test]
+#[serial]
+fn files_are_restored_if_git_fails() {
+    let project = SimpleProject::setup();
+    for (profile, tag) in [("dep", "dep-{{ new_version }}"), ("project", "bad..tag")] {
+        project.extend_profile(
+            profile,
+            &format!(
+                r#"
+[git]
+commit = true
+tag = "{}"
+"#,
+                tag
+            ),
+        );
+    }
+    project.init_git();
+    let req = std::fs::read_to_string(project.path.join("req.txt")).unwrap();
+    let versions = project.fetch_versions();
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "--profiles", "dep,project", "patch"]).handle(),
+        70
+    );
+    assert_eq!(project.git(&["rev-list", "--count", "HEAD"]), "1");
+    assert_eq!(project.git(&["tag"]), "");
+    assert_eq!(project.git(&["status", "--porcelain"]), "");
+    assert_eq!(
+        std::fs::read_to_string(project.path.join("req.txt")).unwrap(),
+        req
+    );
+    assert_eq!(project.fetch_versions(), versions);
+}
+
+#[test]
+fn profile_is_not_mixed_with_groups() {
+    assert!(CLI::try_parse_from(["weee", "bump", "dep", "patch", "--group", "release"]).is_err());
+    assert!(CLI::try_parse_from(["weee", "bump", "patch"]).is_err());
+    assert!(CLI::try_parse_from(["weee", "bump", "--profiles", "dep,project", "patch"]).is_ok());
+}
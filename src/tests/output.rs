@@ -0,0 +1,77 @@
+#![cfg(test)]
+
+use serial_test::serial;
+
+use crate::tests::utils::simple_project::{SimpleProject, SimpleProjectVersions};
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn bump_json_report() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[hooks.afterwords]
+greet = ["echo", "!FORMAT:{old.minor} -> {new.minor}"]
+"#,
+    );
+    let output = project.run_binary(&["bump", "dep", "minor", "--output", "json"]);
+    assert!(output.status.success());
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["profile"], "dep");
+    assert_eq!(report["bumped_part"], "minor");
+    assert_eq!(report["old"]["version"], "0.1.0.alpha.0");
+    assert_eq!(report["new"]["parts"]["minor"], 2);
+    assert_eq!(
+        report["files"],
+        serde_json::json!([
+            {
+                "path": "req.txt",
+                "old_version": "0.1.0-alpha0",
+                "new_version": "0.2.0-alpha0",
+                "lines": [1]
+            },
+            {
+                "path": "req.txt",
+                "old_version": "0.1",
+                "new_version": "0.2",
+                "lines": [2]
+            }
+        ])
+    );
+    assert_eq!(report["hooks"][0]["name"], "greet");
+    assert_eq!(report["hooks"][0]["exit_code"], 0);
+    assert_eq!(report["hooks"][0]["stdout"], "1 -> 2\n");
+
+    assert_eq!(
+        SimpleProjectVersions {
+            project: "14.23.5645b3".into(),
+            dep: "0.2.0-alpha0".into(),
+            dep_another_style: "0.2".into()
+        },
+        project.fetch_versions()
+    );
+}
+
+#[test]
+#[serial]
+fn read_only_json_report() {
+    let project = SimpleProject::setup();
+    let output = project.run_binary(&[
+        "bump",
+        "project",
+        "major",
+        "--read-only",
+        "--output",
+        "json",
+    ]);
+
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["read_only"], true);
+    assert_eq!(report["new"]["version"], "15.0.0.a.0");
+    assert_eq!(report["files"][0]["lines"], serde_json::json!([1]));
+    assert_eq!(report["hooks"], serde_json::json!([]));
+    assert_eq!(project.fetch_versions().project, "14.23.5645b3");
+}
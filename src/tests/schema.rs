@@ -0,0 +1,163 @@
+#![cfg(test)]
+
+use serde_json::Value;
+
+use crate::diagnostics::check_profile;
+use crate::schema::profile_schema;
+
+/// Just enough of JSON Schema to check the keywords `schema` uses
+fn is_valid(schema: &Value, root: &Value, value: &Value) -> bool {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference.trim_start_matches("#/definitions/");
+        if !is_valid(&root["definitions"][name], root, value) {
+            return false;
+        }
+    }
+    if let Some(variants) = schema["oneOf"].as_array() {
+        let matched = variants
+            .iter()
+            .filter(|variant| is_valid(variant, root, value))
+            .count();
+        if matched != 1 {
+            return false;
+        }
+    }
+    if let Some(variants) = schema["anyOf"].as_array() {
+        if !variants
+            .iter()
+            .any(|variant| is_valid(variant, root, value))
+        {
+            return false;
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            return false;
+        }
+    }
+    let type_matches = match schema["type"].as_str() {
+        Some("object") => value.is_object(),
+        Some("array") => value.is_array(),
+        Some("string") => value.is_string(),
+        Some("boolean") => value.is_boolean(),
+        Some("integer") => value.is_u64(),
+        _ => true,
+    };
+    if !type_matches {
+        return false;
+    }
+    if let Some(object) = value.as_object() {
+        for required in schema["required"].as_array().into_iter().flatten() {
+            if !object.contains_key(required.as_str().unwrap()) {
+                return false;
+            }
+        }
+        for (key, item) in object {
+            let item_schema = match schema["properties"].get(key) {
+                Some(item_schema) => item_schema,
+                None => match &schema["additionalProperties"] {
+                    Value::Bool(false) => return false,
+                    Value::Null => continue,
+                    item_schema => item_schema,
+                },
+            };
+            if !is_valid(item_schema, root, item) {
+                return false;
+            }
+        }
+    }
+    if let Some(items) = value.as_array() {
+        if items.len() < schema["minItems"].as_u64().unwrap_or(0) as usize {
+            return false;
+        }
+        if schema["items"].is_object()
+            && !items
+                .iter()
+                .all(|item| is_valid(&schema["items"], root, item))
+        {
+            return false;
+        }
+    }
+    true
+}
+
+fn validate(content: &str) -> bool {
+    let value: toml::Value = toml::from_str(content).unwrap();
+    let value = serde_json::to_value(value).unwrap();
+    let schema = profile_schema();
+    is_valid(&schema, &schema, &value)
+}
+
+#[test]
+fn test_profiles_match_schema() {
+    for profile in ["dep", "project"] {
+        let content = std::fs::read_to_string(format!(
+            "src/tests/projects/simple/.weee/{}.version.toml",
+            profile
+        ))
+        .unwrap();
+        assert!(check_profile(&content).is_ok());
+        assert!(validate(&content), "{} does not match the schema", profile);
+    }
+}
+
+#[test]
+fn every_section_is_described() {
+    let content = r#"
+[parts.major]
+value = 1
+factory = { name = "increment", payload = { default = 0 } }
+
+[parts.stage]
+value = "rc"
+factory = { name = "loop", payload = ["beta", "rc"] }
+
+[[files."Cargo.toml"]]
+version = { view = "{major}-{stage}", placement = "version = \"{version}\"" }
+replaces_count = 1
+enable_liquid_tempaltes = false
+
+[hooks]
+save_logs = true
+before = { test = ["cargo", "test"] }
+afterwords = { push = { shell = "git push", retries = 2, when = "{{ new.stage == 'rc' }}" } }
+
+[git]
+commit = true
+tag = "v{{ new_version }}"
+
+[conventional_commits]
+types = { feat = "major" }
+
+[changelog]
+from_commits = true
+sections = { feat = "Added" }
+
+[dependencies.mylib]
+bump = "major"
+files = { "Cargo.toml" = [{ version = { view = "^{major}", placement = "mylib = \"{version}\"" } }] }
+"#;
+    assert!(check_profile(content).is_ok());
+    assert!(validate(content));
+
+    // Values of inherited parts are stored in the profile's own file
+    assert!(validate(
+        "extends = \"base\"\ninclude = [\"hooks.toml\"]\n[parts.patch]\nvalue = 4\n"
+    ));
+
+    let invalid = [
+        // Unknown factory
+        "[parts.a]\nvalue = 1\nfactory.name = \"incremnt\"\n[files]\n",
+        // Loop payload is required
+        "[parts.a]\nvalue = 1\nfactory.name = \"loop\"\n[files]\n",
+        // The option is spelled `enable_liquid_tempaltes`
+        "[parts]\n[[files.\"a\"]]\nversion = { view = \"\", placement = \"\" }\nenable_liquid_templates = true\n",
+        // Hooks need either `args` or `shell`
+        "[parts]\n[files]\n[hooks.before]\na = { cwd = \"x\" }\n",
+        // Dependencies have no parts of their own
+        "[parts]\n[files]\n[dependencies.a]\nparts = {}\n",
+    ];
+    for content in invalid {
+        assert!(!validate(content), "{}", content);
+    }
+}
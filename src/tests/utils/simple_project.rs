@@ -1,12 +1,11 @@
 #![cfg(test)]
-use fs_extra;
 use toml_edit::Document;
 
 #[cfg(not(target_os = "windows"))]
-const SIMPLE_PROJECT_PATH: &'static str = "src/tests/projects/simple";
+const SIMPLE_PROJECT_PATH: &str = "src/tests/projects/simple";
 
 #[cfg(target_os = "windows")]
-const SIMPLE_PROJECT_PATH: &'static str = r#"src\tests\projects\simple"#;
+const SIMPLE_PROJECT_PATH: &str = r#"src\tests\projects\simple"#;
 
 pub struct SimpleProject {
     pub path: std::path::PathBuf,
@@ -36,9 +35,55 @@ impl SimpleProject {
 }
 
 impl SimpleProject {
+    /// Append TOML content (i.e. hooks) to a profile's config
+    pub fn extend_profile(&self, profile: &str, content: &str) {
+        let profile_path = self
+            .path
+            .join(".weee")
+            .join(format!("{}.version.toml", profile));
+        let mut config = std::fs::read_to_string(&profile_path).expect("Cannot read profile");
+        config.push_str(content);
+        std::fs::write(&profile_path, config).expect("Cannot write profile");
+    }
+
+    /// Run git inside the project and return its trimmed stdout
+    pub fn git(&self, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .args(args)
+            .current_dir(&self.path)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {:?} has failed", args);
+        String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string()
+    }
+
+    /// Make the project a git repository with everything committed
+    pub fn init_git(&self) {
+        self.git(&["init", "--quiet"]);
+        self.git(&["config", "user.name", "weee"]);
+        self.git(&["config", "user.email", "weee@example.com"]);
+        self.git(&["config", "commit.gpgsign", "false"]);
+        self.git(&["config", "tag.gpgsign", "false"]);
+        self.git(&["add", "--all"]);
+        self.git(&["commit", "--quiet", "-m", "Initial commit"]);
+    }
+
+    /// Run weee's binary as a separate process (i.e. to capture its stdout)
+    pub fn run_binary(&self, args: &[&str]) -> std::process::Output {
+        std::process::Command::new("cargo")
+            .arg("run")
+            .arg("-q")
+            .arg("--")
+            .args(args)
+            .output()
+            .expect("Failed to run weee binary")
+    }
+
     pub fn fetch_versions(&self) -> SimpleProjectVersions {
         dbg!(&self.path);
-        let req = std::fs::read_to_string(&self.path.join("req.txt"))
+        let req = std::fs::read_to_string(self.path.join("req.txt"))
             .expect("Cannot read req.txt content");
         let req = req.trim_end();
 
@@ -54,7 +99,7 @@ impl SimpleProject {
             .expect("Invalid version separator for req.txt")
             .1;
 
-        let pyproject = std::fs::read_to_string(&self.path.join("pyproject.toml"))
+        let pyproject = std::fs::read_to_string(self.path.join("pyproject.toml"))
             .expect("Cannot read req.txt content");
 
         let pyproject_doc = pyproject
@@ -62,10 +107,7 @@ impl SimpleProject {
             .expect("Invalid TOML syntax for pyproject.toml");
 
         let pyproject_version = match &pyproject_doc["project"]["version"] {
-            toml_edit::Item::Value(val) => match val {
-                toml_edit::Value::String(val) => val,
-                _ => unreachable!("Invalid type for version in pyproject.toml"),
-            },
+            toml_edit::Item::Value(toml_edit::Value::String(val)) => val,
             _ => unreachable!("Invalid type for version in pyproject.toml"),
         };
 
@@ -75,4 +117,13 @@ impl SimpleProject {
             project: pyproject_version.value().clone(),
         }
     }
+
+    /// Versions the project is set up with
+    pub fn untouched_versions() -> SimpleProjectVersions {
+        SimpleProjectVersions {
+            project: "14.23.5645b3".into(),
+            dep: "0.1.0-alpha0".into(),
+            dep_another_style: "0.1".into(),
+        }
+    }
 }
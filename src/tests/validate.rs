@@ -0,0 +1,98 @@
+#![cfg(test)]
+
+use clap::Parser;
+use serial_test::serial;
+
+use crate::commands::CLI;
+use crate::tests::utils::simple_project::SimpleProject;
+use crate::{RTContext, ValidateOperation};
+
+#[test]
+#[serial]
+fn valid_profiles_pass() {
+    SimpleProject::setup();
+    assert_eq!(CLI::parse_from(["weee", "validate", "--all"]).handle(), 0);
+}
+
+#[test]
+#[serial]
+fn problems_are_collected() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[[files."missing.txt"]]
+version.view = "{{ major }"
+version.placement = "{version}"
+enable_liquid_templates = true
+
+[hooks.afterwords]
+notify = ["weee-no-such-executable"]
+"#,
+    );
+
+    let reports = ValidateOperation::all()
+        .run(&RTContext::at(&project.path))
+        .unwrap();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].profile, "dep");
+    assert_eq!(reports[1].profile, "project");
+    assert!(reports[1].problems.is_empty());
+
+    let checks: Vec<_> = reports[0]
+        .problems
+        .iter()
+        .map(|problem| problem.check)
+        .collect();
+    assert_eq!(checks, vec!["spelling", "files", "hooks"]);
+    assert!(reports[0].problems[0]
+        .message
+        .contains("Did you mean `enable_liquid_tempaltes`?"));
+
+    assert_eq!(CLI::parse_from(["weee", "validate", "dep"]).handle(), 19);
+}
+
+#[test]
+#[serial]
+fn config_errors_have_positions() {
+    let project = SimpleProject::setup();
+    project.extend_profile(
+        "dep",
+        r#"
+[[files."req.txt"]]
+version.view = "{major}.{build}"
+version.placement = "{version}"
+
+[git]
+commit = true
+tag = "v{{ new_version"
+"#,
+    );
+
+    let reports = ValidateOperation::new("dep")
+        .run(&RTContext::at(&project.path))
+        .unwrap();
+    let problems = &reports[0].problems;
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].check, "config");
+    assert!(problems[0].message.contains("`{build}`"));
+    assert!(problems[0].line.is_some());
+}
+
+#[test]
+#[serial]
+fn tag_requires_commit() {
+    let project = SimpleProject::setup();
+    project.extend_profile("dep", "\n[git]\ntag = \"v{{ new_version }}\"\n");
+
+    let reports = ValidateOperation::new("dep")
+        .run(&RTContext::at(&project.path))
+        .unwrap();
+    let problems = &reports[0].problems;
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].message.contains("`git.commit = true`"));
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "dep", "patch"]).handle(),
+        18
+    );
+}
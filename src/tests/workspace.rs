@@ -0,0 +1,219 @@
+#![cfg(test)]
+
+use clap::Parser;
+use serial_test::serial;
+
+use crate::commands::CLI;
+use crate::tests::utils::simple_project::SimpleProject;
+use crate::workspace;
+use crate::{BumpOperation, MultiBumpOperation, RTContext};
+
+/// Copies of the simple project in `packages/api` and `packages/cli`
+/// listed in the root's workspace file
+fn setup_workspace() -> SimpleProject {
+    let project = SimpleProject::setup();
+    let packages_path = project.path.join("packages");
+    std::fs::create_dir(&packages_path).unwrap();
+    for member in ["api", "cli"] {
+        fs_extra::dir::copy(
+            "src/tests/projects/simple",
+            &packages_path,
+            &fs_extra::dir::CopyOptions::new(),
+        )
+        .unwrap();
+        std::fs::rename(packages_path.join("simple"), packages_path.join(member)).unwrap();
+    }
+    // Not a member: there is no `.weee`
+    std::fs::create_dir(packages_path.join("docs")).unwrap();
+    std::fs::write(
+        project.path.join(".weee").join(workspace::WORKSPACE_FILE),
+        "members = [\"packages/*\"]\n",
+    )
+    .unwrap();
+    project
+}
+
+#[test]
+#[serial]
+fn nearest_weee_is_discovered() {
+    let project = SimpleProject::setup();
+    let nested = project.path.join("src").join("deep");
+    std::fs::create_dir_all(&nested).unwrap();
+
+    assert_eq!(workspace::discover(&nested), Some(project.path.clone()));
+    assert_eq!(
+        workspace::discover(&project.path),
+        Some(project.path.clone())
+    );
+    assert_eq!(
+        workspace::discover(&std::env::temp_dir().join("simple-none")),
+        None
+    );
+}
+
+#[test]
+#[serial]
+fn workspace_root_is_discovered_from_members() {
+    let project = setup_workspace();
+    let member = project.path.join("packages/api");
+    let nested = member.join("src");
+    std::fs::create_dir_all(&nested).unwrap();
+
+    assert_eq!(workspace::discover(&member), Some(project.path.clone()));
+    assert_eq!(workspace::discover(&nested), Some(project.path.clone()));
+
+    // The root's profiles and history are used from inside a member
+    std::env::remove_var("WEEE_PROJECT_PATH");
+    std::env::set_current_dir(&member).unwrap();
+    let code = CLI::parse_from(["weee", "bump", "packages/api:dep", "minor"]).handle();
+    std::env::set_current_dir(env!("CARGO_MANIFEST_DIR")).unwrap();
+    assert_eq!(code, 0);
+    let member_req = std::fs::read_to_string(member.join("req.txt")).unwrap();
+    assert!(member_req.starts_with("dep==0.2.0-alpha0"));
+    assert!(project.path.join(".weee/history.jsonl").exists());
+    assert!(!member.join(".weee/history.jsonl").exists());
+}
+
+#[test]
+#[serial]
+fn members_are_resolved_relative_to_their_roots() {
+    let project = setup_workspace();
+    let ctx = RTContext::at(&project.path);
+
+    let names = ctx.profile_names().unwrap();
+    assert_eq!(
+        names,
+        vec![
+            "dep",
+            "project",
+            "packages/api:dep",
+            "packages/api:project",
+            "packages/cli:dep",
+            "packages/cli:project"
+        ]
+    );
+
+    let report = BumpOperation::new("packages/api:dep", "minor")
+        .run(&ctx)
+        .unwrap();
+    assert_eq!(report.profile, "packages/api:dep");
+    assert_eq!(report.new.version, "0.2.0.alpha.0");
+
+    let member_req = std::fs::read_to_string(project.path.join("packages/api/req.txt")).unwrap();
+    assert!(member_req.starts_with("dep==0.2.0-alpha0"));
+    assert_eq!(project.fetch_versions().dep, "0.1.0-alpha0");
+    // The bump is recorded by the root and can be undone there
+    assert!(!project
+        .path
+        .join("packages/api/.weee/history.jsonl")
+        .exists());
+    let output = project.run_binary(&["history", "packages/api:dep", "--output", "json"]);
+    let entries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(entries.as_array().unwrap().len(), 1);
+    assert_eq!(CLI::parse_from(["weee", "undo"]).handle(), 0);
+    let member_req = std::fs::read_to_string(project.path.join("packages/api/req.txt")).unwrap();
+    assert!(member_req.starts_with("dep==0.1.0-alpha0"));
+
+    assert_eq!(CLI::parse_from(["weee", "validate", "--all"]).handle(), 0);
+}
+
+#[test]
+#[serial]
+fn unknown_member_is_reported() {
+    let project = setup_workspace();
+
+    let error = match RTContext::at(&project.path).fetch_profile_conext("packages/docs:dep") {
+        Ok(_) => panic!("Directory without `.weee` is a member"),
+        Err(error) => error,
+    };
+    assert_eq!(error.title(), "NoSuchWorkspaceMember");
+    assert_eq!(
+        CLI::parse_from(["weee", "show", "packages/web:project"]).handle(),
+        93
+    );
+
+    std::fs::write(
+        project.path.join(".weee").join(workspace::WORKSPACE_FILE),
+        "member = [\"packages/*\"]\n",
+    )
+    .unwrap();
+    assert_eq!(
+        CLI::parse_from(["weee", "show", "packages/api:project"]).handle(),
+        92
+    );
+}
+
+#[test]
+#[serial]
+fn member_profiles_are_listed_by_patterns() {
+    let project = setup_workspace();
+    std::fs::write(
+        project.path.join(".weee/groups.toml"),
+        "deps = [\"packages/*:dep\"]\nmissing = [\"packages/*:app\"]\n",
+    )
+    .unwrap();
+
+    let reports = MultiBumpOperation::group("deps", "minor")
+        .run(&RTContext::at(&project.path))
+        .unwrap();
+    let profiles: Vec<_> = reports
+        .iter()
+        .map(|report| report.profile.as_str())
+        .collect();
+    assert_eq!(profiles, vec!["packages/api:dep", "packages/cli:dep"]);
+    assert_eq!(project.fetch_versions().dep, "0.1.0-alpha0");
+
+    assert_eq!(
+        CLI::parse_from([
+            "weee",
+            "bump",
+            "--profiles",
+            "project,packages/c?i:*",
+            "patch"
+        ])
+        .handle(),
+        0
+    );
+    let cli_req = std::fs::read_to_string(project.path.join("packages/cli/req.txt")).unwrap();
+    assert!(cli_req.starts_with("dep==0.2.1-alpha0"));
+    assert_eq!(project.fetch_versions().project, "14.23.5646a0");
+
+    assert_eq!(
+        CLI::parse_from(["weee", "bump", "--group", "missing", "patch"]).handle(),
+        103
+    );
+}
+
+#[test]
+#[serial]
+fn profiles_of_different_repositories_are_not_committed_together() {
+    let project = setup_workspace();
+    let member = SimpleProject {
+        path: project.path.join("packages/api"),
+    };
+    let git = "\n[git]\ncommit = true\n";
+    project.extend_profile("dep", git);
+    member.extend_profile("dep", git);
+    member.init_git();
+    project.init_git();
+    let req_before = std::fs::read_to_string(member.path.join("req.txt")).unwrap();
+
+    assert_eq!(
+        CLI::parse_from([
+            "weee",
+            "bump",
+            "--profiles",
+            "dep,packages/api:dep",
+            "patch"
+        ])
+        .handle(),
+        76
+    );
+    assert_eq!(project.fetch_versions().dep, "0.1.0-alpha0");
+    assert_eq!(
+        std::fs::read_to_string(member.path.join("req.txt")).unwrap(),
+        req_before
+    );
+    assert_eq!(member.git(&["rev-list", "--count", "HEAD"]), "1");
+    assert_eq!(project.git(&["rev-list", "--count", "HEAD"]), "1");
+}
@@ -0,0 +1,332 @@
+use toml_edit::{Document, Item, TableLike};
+
+use crate::composition;
+use crate::config::{Hook, DEFAULT_CHANGELOG_PATH};
+use crate::context::{when_template, HookStage, ProfileContext, RTContext};
+use crate::diagnostics::Diagnostic;
+use crate::error::Error;
+use crate::handleable::CmdResult;
+use crate::report::Problem;
+
+const FILE_OPTIONS: [&str; 3] = ["version", "replaces_count", "enable_liquid_tempaltes"];
+const FILE_VERSION_OPTIONS: [&str; 2] = ["view", "placement"];
+
+/// Run every static check of a profile. Nothing in the project is changed
+/// and nothing is executed. Checks which need a parsed config
+/// are skipped if the config is invalid
+pub fn validate_profile(ctx: &RTContext, profile: &str) -> CmdResult<Vec<Problem>> {
+    let composed = match composition::compose(ctx, profile) {
+        Ok(composed) => composed,
+        Err(Error::TOMLInvalidSyntax { source, path, .. }) => {
+            let position = source.line_col();
+            return Ok(vec![Problem {
+                check: "syntax",
+                message: source.to_string(),
+                line: position.map(|(line, _column)| line + 1),
+                column: position.map(|(_line, column)| column + 1),
+                file: Some(path.display().to_string())
+                    .filter(|_file| path != ctx.profile_path(profile)),
+            }]);
+        }
+        Err(Error::InvalidProfileConfig { diagnostics, .. }) => {
+            return Ok(config_problems(diagnostics));
+        }
+        Err(err) => return Err(err),
+    };
+
+    let mut problems = check_spelling(&composed.merged);
+    let profile_model = match composed.check() {
+        Ok(model) => model,
+        Err(diagnostics) => {
+            problems.extend(config_problems(diagnostics));
+            return Ok(problems);
+        }
+    };
+
+    let profile_ctx = ProfileContext {
+        rt_context: ctx,
+        profile_model,
+        profile_doc: composed.own,
+        profile_name: profile.to_string(),
+    };
+    problems.extend(check_files(&profile_ctx));
+    problems.extend(check_templates(&profile_ctx));
+    problems.extend(check_hooks(&profile_ctx));
+    Ok(problems)
+}
+
+fn config_problems(diagnostics: Vec<Diagnostic>) -> Vec<Problem> {
+    diagnostics
+        .into_iter()
+        .map(|diagnostic| Problem {
+            check: "config",
+            message: diagnostic.message,
+            line: Some(diagnostic.line),
+            column: Some(diagnostic.column),
+            file: diagnostic.path.map(|path| path.display().to_string()),
+        })
+        .collect()
+}
+
+fn problem(check: &'static str, message: String) -> Problem {
+    Problem {
+        check,
+        message,
+        line: None,
+        column: None,
+        file: None,
+    }
+}
+
+/// Unknown options of files are silently ignored when the profile is loaded,
+/// so a misspelled option (the liquid one is `enable_liquid_tempaltes`) has no effect
+fn check_spelling(profile_doc: &Document) -> Vec<Problem> {
+    let mut problems = vec![];
+    let files = match profile_doc.get("files").and_then(Item::as_table_like) {
+        Some(files) => files,
+        None => return problems,
+    };
+    for (file_name, views) in files.iter() {
+        let views: Vec<&dyn TableLike> = match views {
+            Item::ArrayOfTables(views) => views.iter().map(|view| view as &dyn TableLike).collect(),
+            Item::Value(toml_edit::Value::Array(views)) => views
+                .iter()
+                .filter_map(|view| view.as_inline_table())
+                .map(|view| view as &dyn TableLike)
+                .collect(),
+            _ => continue,
+        };
+        for view in views {
+            for (key, value) in view.iter() {
+                if !FILE_OPTIONS.contains(&key) {
+                    problems.push(unknown_option(file_name, key, &FILE_OPTIONS));
+                } else if key == "version" {
+                    for (key, _value) in value.as_table_like().into_iter().flat_map(|t| t.iter()) {
+                        if !FILE_VERSION_OPTIONS.contains(&key) {
+                            problems.push(unknown_option(file_name, key, &FILE_VERSION_OPTIONS));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    problems
+}
+
+fn unknown_option(file_name: &str, key: &str, known: &[&str]) -> Problem {
+    let suggestion = known.iter().find(|option| is_misspelling(key, option));
+    let message = match suggestion {
+        Some(option) => format!(
+            "Unknown option `{}` of `{}` is ignored. Did you mean `{}`?",
+            key, file_name, option
+        ),
+        None => format!("Unknown option `{}` of `{}` is ignored", key, file_name),
+    };
+    problem("spelling", message)
+}
+
+fn is_misspelling(key: &str, option: &str) -> bool {
+    let common_prefix = key
+        .chars()
+        .zip(option.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    edit_distance(key, option) <= (option.len() / 4).max(1) || common_prefix >= option.len().min(13)
+}
+
+/// Levenshtein distance counting a swap of two neighbouring characters as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+fn check_files(profile_ctx: &ProfileContext) -> Vec<Problem> {
+    let mut files: Vec<&str> = profile_ctx
+        .profile_model
+        .files
+        .keys()
+        .map(String::as_str)
+        .collect();
+    if let Some(changelog) = &profile_ctx.profile_model.changelog {
+        files.push(changelog.path.as_deref().unwrap_or(DEFAULT_CHANGELOG_PATH));
+    }
+    files
+        .into_iter()
+        .filter(|file| !profile_ctx.rt_context.project_file_path(file).exists())
+        .map(|file| problem("files", format!("File `{}` does not exist", file)))
+        .collect()
+}
+
+fn check_templates(profile_ctx: &ProfileContext) -> Vec<Problem> {
+    let config = &profile_ctx.profile_model;
+    let mut templates: Vec<(String, String)> = vec![];
+    for (file_name, views) in config.files.iter() {
+        for file in views.iter() {
+            if file.enable_liquid_tempaltes.unwrap_or_default() {
+                templates.push((
+                    format!("view of `{}`", file_name),
+                    file.version.view.clone(),
+                ));
+            }
+        }
+    }
+    if let Some(git) = &config.git {
+        let options = [
+            ("git.commit_message", &git.commit_message),
+            ("git.tag", &git.tag),
+            ("git.tag_message", &git.tag_message),
+        ];
+        for (origin, template) in options {
+            if let Some(template) = template {
+                templates.push((format!("`{}`", origin), template.clone()));
+            }
+        }
+    }
+    if let Some(heading) = config
+        .changelog
+        .as_ref()
+        .and_then(|changelog| changelog.heading.as_ref())
+    {
+        templates.push(("`changelog.heading`".into(), heading.clone()));
+    }
+    for (stage, name, hook) in hooks(profile_ctx) {
+        let detailed = hook.detailed();
+        if let Some(when) = detailed.and_then(|hook| hook.when.as_ref()) {
+            templates.push((
+                format!("`when` of hook `{}` ({})", name, stage.name()),
+                when_template(when),
+            ));
+        }
+        for arg in hook.command().unwrap_or_default() {
+            if let Some(formatable) = arg.strip_prefix("!FORMAT:") {
+                if formatable.contains("{{") || formatable.contains("{%") {
+                    templates.push((
+                        format!("argument of hook `{}` ({})", name, stage.name()),
+                        formatable.to_string(),
+                    ));
+                }
+            }
+        }
+    }
+
+    let parser = liquid::ParserBuilder::with_stdlib().build().unwrap();
+    templates
+        .into_iter()
+        .filter_map(|(origin, template)| match parser.parse(&template) {
+            Ok(_template) => None,
+            Err(err) => Some(problem(
+                "liquid",
+                format!(
+                    "Template in {} cannot be compiled: {}",
+                    origin,
+                    err.to_string().trim_end()
+                ),
+            )),
+        })
+        .collect()
+}
+
+fn check_hooks(profile_ctx: &ProfileContext) -> Vec<Problem> {
+    let mut problems = vec![];
+    for (stage, name, hook) in hooks(profile_ctx) {
+        let args = match hook.command() {
+            Ok(args) => args,
+            Err(reason) => {
+                problems.push(problem(
+                    "hooks",
+                    format!("Hook `{}` ({}): {}", name, stage.name(), reason),
+                ));
+                continue;
+            }
+        };
+        let program = &args[0];
+        // Argument sources are resolved only when the hook is executed
+        if program.starts_with('!') {
+            continue;
+        }
+        if program.contains('/') || program.contains('\\') {
+            let cwd = hook
+                .detailed()
+                .and_then(|hook| hook.cwd.as_deref())
+                .unwrap_or(".");
+            if !profile_ctx
+                .rt_context
+                .project_file_path(cwd)
+                .join(program)
+                .exists()
+            {
+                problems.push(problem(
+                    "hooks",
+                    format!(
+                        "Executable `{}` of hook `{}` ({}) does not exist",
+                        program,
+                        name,
+                        stage.name()
+                    ),
+                ));
+            }
+        } else if !is_in_path(program) {
+            problems.push(problem(
+                "hooks",
+                format!(
+                    "Executable `{}` of hook `{}` ({}) is not found in PATH",
+                    program,
+                    name,
+                    stage.name()
+                ),
+            ));
+        }
+    }
+    problems
+}
+
+fn hooks<'a>(profile_ctx: &'a ProfileContext) -> Vec<(HookStage, &'a String, &'a Hook)> {
+    let hooks = match &profile_ctx.profile_model.hooks {
+        Some(hooks) => hooks,
+        None => return vec![],
+    };
+    let stages = [
+        (HookStage::Before, &hooks.before),
+        (HookStage::Afterwords, &hooks.afterwords),
+        (HookStage::OnFailure, &hooks.on_failure),
+    ];
+    let mut found = vec![];
+    for (stage, stage_hooks) in stages {
+        for (name, hook) in stage_hooks.iter().flat_map(|hooks| hooks.iter()) {
+            found.push((stage, name, hook));
+        }
+    }
+    found
+}
+
+fn is_in_path(program: &str) -> bool {
+    let path = match std::env::var_os("PATH") {
+        Some(path) => path,
+        None => return false,
+    };
+    std::env::split_paths(&path).any(|dir| {
+        let candidate = dir.join(program);
+        candidate.is_file()
+            || (cfg!(target_os = "windows") && candidate.with_extension("exe").is_file())
+    })
+}
@@ -0,0 +1,179 @@
+//! Projects made of several sub-projects, each with its own `.weee`:
+//!
+//! ```toml
+//! # .weee/workspace.toml
+//! members = ["packages/*", "tools/cli"]
+//! ```
+//!
+//! Members are directories relative to the workspace's root (`/`-separated,
+//! `*` and `?` match within one path segment). Directories without `.weee` are skipped.
+//! A member's profile is referred to as `<member>:<profile>`, i.e. `packages/api:project`,
+//! and its files, hooks and history are resolved relative to the member's directory.
+
+use std::path::{Path, PathBuf};
+
+use serde_derive::Deserialize;
+
+use crate::diagnostics::Diagnostic;
+use crate::error::Error;
+use crate::handleable::CmdResult;
+
+pub const WORKSPACE_FILE: &str = "workspace.toml";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WorkspaceConfig {
+    members: Vec<String>,
+}
+
+/// The nearest directory with `.weee` starting from `start` and going up, like git does.
+/// When it's a member of a workspace further up, the workspace's root is taken instead
+pub fn discover(start: &Path) -> Option<PathBuf> {
+    let mut root = start
+        .ancestors()
+        .find(|dir| dir.join(".weee").is_dir())?
+        .to_path_buf();
+    while let Some(workspace) = root
+        .ancestors()
+        .skip(1)
+        .filter(|dir| dir.join(".weee").is_dir())
+        .find(|dir| {
+            members(dir)
+                .unwrap_or_default()
+                .iter()
+                .any(|member| dir.join(member) == root)
+        })
+    {
+        root = workspace.to_path_buf();
+    }
+    Some(root)
+}
+
+/// `/`-separated paths of members relative to the root, sorted.
+/// No workspace file means there are no members
+pub fn members(root: &Path) -> CmdResult<Vec<String>> {
+    let path = root.join(".weee").join(WORKSPACE_FILE);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(Error::CannotReadWorkspaceFile { path, source: err }),
+    };
+    let config = match toml::from_str::<WorkspaceConfig>(&content) {
+        Ok(config) => config,
+        Err(err) => {
+            return Err(Error::InvalidWorkspaceFile {
+                diagnostics: vec![Diagnostic::from_toml_error(&content, &err)],
+                path,
+            })
+        }
+    };
+
+    let mut members = vec![];
+    for pattern in config.members.iter() {
+        let segments: Vec<&str> = pattern
+            .split('/')
+            .filter(|segment| !segment.is_empty() && *segment != ".")
+            .collect();
+        expand(root, &segments, String::new(), &mut members);
+    }
+    members.sort();
+    members.dedup();
+    Ok(members)
+}
+
+/// Collect directories under `dir` matching the rest of a pattern's segments
+fn expand(dir: &Path, segments: &[&str], prefix: String, found: &mut Vec<String>) {
+    let (segment, rest) = match segments.split_first() {
+        Some(split) => split,
+        None => {
+            if !prefix.is_empty() && dir.join(".weee").is_dir() {
+                found.push(prefix);
+            }
+            return;
+        }
+    };
+    let join = |name: &str| {
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", prefix, name)
+        }
+    };
+
+    if !segment.contains(['*', '?']) {
+        expand(&dir.join(segment), rest, join(segment), found);
+        return;
+    }
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_err) => return,
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = match entry.file_name().to_str() {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        // Hidden directories are matched explicitly only
+        if name.starts_with('.') || !entry.path().is_dir() || !matches(segment, &name) {
+            continue;
+        }
+        expand(&entry.path(), rest, join(&name), found);
+    }
+}
+
+/// Whether a profile's name (`<member>:<profile>` for members) matches a pattern
+/// with `*` and `?` in its segments, i.e. `packages/*:project`
+pub fn matches_profile(pattern: &str, profile: &str) -> bool {
+    let (pattern_member, pattern_name) = match pattern.split_once(':') {
+        Some((member, name)) => (Some(member), name),
+        None => (None, pattern),
+    };
+    let (member, name) = match profile.split_once(':') {
+        Some((member, name)) => (Some(member), name),
+        None => (None, profile),
+    };
+    let members_match = match (pattern_member, member) {
+        (Some(pattern_member), Some(member)) => {
+            let pattern_segments: Vec<&str> = pattern_member.split('/').collect();
+            let segments: Vec<&str> = member.split('/').collect();
+            pattern_segments.len() == segments.len()
+                && pattern_segments
+                    .iter()
+                    .zip(segments)
+                    .all(|(pattern, segment)| matches(pattern, segment))
+        }
+        (None, None) => true,
+        _ => false,
+    };
+    members_match && matches(pattern_name, name)
+}
+
+/// Glob matching of one path segment with `*` and `?`
+fn matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the part of the name it has consumed
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((star_p, star_n)) => {
+                    p = star_p + 1;
+                    n = star_n + 1;
+                    star = Some((star_p, star_n + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}